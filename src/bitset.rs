@@ -0,0 +1,411 @@
+use std::fmt::{Debug, Formatter};
+use std::ops::{BitAnd, BitOr, BitXor, Not, Shl, Sub};
+
+/// Unsigned integer used as storage for a fixed-size `BitSet`.
+pub trait Bits:
+  Copy
+  + Eq
+  + std::hash::Hash
+  + BitAnd<Output = Self>
+  + BitOr<Output = Self>
+  + BitXor<Output = Self>
+  + Not<Output = Self>
+  + Shl<usize, Output = Self>
+{
+  const BITS: usize;
+  const ZERO: Self;
+  const ONE: Self;
+
+  fn count_ones(self) -> u32;
+  fn trailing_zeros(self) -> u32;
+}
+
+macro_rules! impl_bits {
+  ($($t:ty),*) => {
+    $(
+      impl Bits for $t {
+        const BITS: usize = <$t>::BITS as usize;
+        const ZERO: Self = 0;
+        const ONE: Self = 1;
+
+        fn count_ones(self) -> u32 {
+          <$t>::count_ones(self)
+        }
+
+        fn trailing_zeros(self) -> u32 {
+          <$t>::trailing_zeros(self)
+        }
+      }
+    )*
+  };
+}
+
+impl_bits!(u32, u64, u128);
+
+/// Set of small integers, `0..T::BITS`, packed in a single word.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitSet<T: Bits = u64>(T);
+
+pub type BitSet64 = BitSet<u64>;
+pub type BitSet128 = BitSet<u128>;
+
+impl<T: Bits> Default for BitSet<T> {
+  fn default() -> Self {
+    Self(T::ZERO)
+  }
+}
+
+impl<T: Bits> Debug for BitSet<T> {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    f.debug_set().entries(self.iter()).finish()
+  }
+}
+
+impl<T: Bits> BitSet<T> {
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  pub fn from_bits(bits: T) -> Self {
+    Self(bits)
+  }
+
+  pub fn bits(&self) -> T {
+    self.0
+  }
+
+  fn mask(i: usize) -> T {
+    assert!(i < T::BITS, "{i} does not fit in a {}-bit set", T::BITS);
+    T::ONE << i
+  }
+
+  pub fn insert(&mut self, i: usize) -> bool {
+    let was_present = self.contains(i);
+    self.0 = self.0 | Self::mask(i);
+    !was_present
+  }
+
+  pub fn remove(&mut self, i: usize) -> bool {
+    let was_present = self.contains(i);
+    self.0 = self.0 & !Self::mask(i);
+    was_present
+  }
+
+  pub fn with(mut self, i: usize) -> Self {
+    self.insert(i);
+    self
+  }
+
+  pub fn contains(&self, i: usize) -> bool {
+    i < T::BITS && self.0 & Self::mask(i) != T::ZERO
+  }
+
+  pub fn len(&self) -> usize {
+    self.0.count_ones() as usize
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.0 == T::ZERO
+  }
+
+  pub fn union(&self, other: &Self) -> Self {
+    Self(self.0 | other.0)
+  }
+
+  pub fn intersection(&self, other: &Self) -> Self {
+    Self(self.0 & other.0)
+  }
+
+  pub fn difference(&self, other: &Self) -> Self {
+    Self(self.0 & !other.0)
+  }
+
+  pub fn symmetric_difference(&self, other: &Self) -> Self {
+    Self(self.0 ^ other.0)
+  }
+
+  pub fn is_subset(&self, other: &Self) -> bool {
+    self.difference(other).is_empty()
+  }
+
+  /// Smallest element.
+  pub fn first(&self) -> Option<usize> {
+    if self.is_empty() {
+      None
+    } else {
+      Some(self.0.trailing_zeros() as usize)
+    }
+  }
+
+  /// Elements in increasing order.
+  pub fn iter(&self) -> BitSetIter<T> {
+    BitSetIter(self.0)
+  }
+}
+
+pub struct BitSetIter<T: Bits>(T);
+
+impl<T: Bits> Iterator for BitSetIter<T> {
+  type Item = usize;
+
+  fn next(&mut self) -> Option<usize> {
+    if self.0 == T::ZERO {
+      return None;
+    }
+    let i = self.0.trailing_zeros() as usize;
+    self.0 = self.0 & !(T::ONE << i);
+    Some(i)
+  }
+}
+
+impl<T: Bits> IntoIterator for BitSet<T> {
+  type Item = usize;
+  type IntoIter = BitSetIter<T>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+impl<T: Bits> FromIterator<usize> for BitSet<T> {
+  fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+    let mut set = Self::new();
+    for i in iter {
+      set.insert(i);
+    }
+    set
+  }
+}
+
+impl<T: Bits> BitAnd for BitSet<T> {
+  type Output = Self;
+
+  fn bitand(self, rhs: Self) -> Self {
+    self.intersection(&rhs)
+  }
+}
+
+impl<T: Bits> BitOr for BitSet<T> {
+  type Output = Self;
+
+  fn bitor(self, rhs: Self) -> Self {
+    self.union(&rhs)
+  }
+}
+
+impl<T: Bits> Sub for BitSet<T> {
+  type Output = Self;
+
+  fn sub(self, rhs: Self) -> Self {
+    self.difference(&rhs)
+  }
+}
+
+const A_UPPER: usize = 'A' as usize;
+const A_LOWER: usize = 'a' as usize;
+
+/// Maps `a..=z` to `1..=26` and `A..=Z` to `27..=52`, the rucksack priorities.
+pub fn letter_index(c: char) -> Option<usize> {
+  match c {
+    'a'..='z' => Some(c as usize - A_LOWER + 1),
+    'A'..='Z' => Some(c as usize - A_UPPER + 27),
+    _ => None,
+  }
+}
+
+/// Inverse of `letter_index`.
+pub fn index_letter(i: usize) -> Option<char> {
+  match i {
+    1..=26 => Some((A_LOWER + i - 1) as u8 as char),
+    27..=52 => Some((A_UPPER + i - 27) as u8 as char),
+    _ => None,
+  }
+}
+
+impl BitSet64 {
+  /// Letters of `s`, indexed by `letter_index`. Other characters are ignored.
+  pub fn from_letters(s: &str) -> Self {
+    s.chars().filter_map(letter_index).collect()
+  }
+
+  pub fn letters(&self) -> impl Iterator<Item = char> {
+    self.iter().filter_map(index_letter)
+  }
+}
+
+/// `BitSet` without an upper bound, backed by as many words as needed.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct GrowableBitSet {
+  words: Vec<u64>,
+}
+
+impl Debug for GrowableBitSet {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    f.debug_set().entries(self.iter()).finish()
+  }
+}
+
+impl GrowableBitSet {
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  pub fn insert(&mut self, i: usize) -> bool {
+    let (word, bit) = (i / 64, i % 64);
+    if word >= self.words.len() {
+      self.words.resize(word + 1, 0);
+    }
+    let was_present = self.words[word] & (1 << bit) != 0;
+    self.words[word] |= 1 << bit;
+    !was_present
+  }
+
+  pub fn remove(&mut self, i: usize) -> bool {
+    let was_present = self.contains(i);
+    if was_present {
+      self.words[i / 64] &= !(1 << (i % 64));
+      self.trim();
+    }
+    was_present
+  }
+
+  pub fn contains(&self, i: usize) -> bool {
+    self
+      .words
+      .get(i / 64)
+      .is_some_and(|w| w & (1 << (i % 64)) != 0)
+  }
+
+  pub fn len(&self) -> usize {
+    self.words.iter().map(|w| w.count_ones() as usize).sum()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.words.iter().all(|&w| w == 0)
+  }
+
+  // Keeps equal sets equal regardless of how large they once grew.
+  fn trim(&mut self) {
+    while self.words.last() == Some(&0) {
+      self.words.pop();
+    }
+  }
+
+  fn zip_with(&self, other: &Self, f: impl Fn(u64, u64) -> u64) -> Self {
+    let n = self.words.len().max(other.words.len());
+    let word = |words: &Vec<u64>, i: usize| words.get(i).copied().unwrap_or(0);
+    let mut result = Self {
+      words: (0..n)
+        .map(|i| f(word(&self.words, i), word(&other.words, i)))
+        .collect(),
+    };
+    result.trim();
+    result
+  }
+
+  pub fn union(&self, other: &Self) -> Self {
+    self.zip_with(other, |a, b| a | b)
+  }
+
+  pub fn intersection(&self, other: &Self) -> Self {
+    self.zip_with(other, |a, b| a & b)
+  }
+
+  pub fn difference(&self, other: &Self) -> Self {
+    self.zip_with(other, |a, b| a & !b)
+  }
+
+  pub fn symmetric_difference(&self, other: &Self) -> Self {
+    self.zip_with(other, |a, b| a ^ b)
+  }
+
+  pub fn is_subset(&self, other: &Self) -> bool {
+    self.difference(other).is_empty()
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+    self
+      .words
+      .iter()
+      .enumerate()
+      .flat_map(|(i, &w)| BitSetIter(w).map(move |bit| i * 64 + bit))
+  }
+}
+
+impl FromIterator<usize> for GrowableBitSet {
+  fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+    let mut set = Self::new();
+    for i in iter {
+      set.insert(i);
+    }
+    set
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{index_letter, letter_index, BitSet, BitSet128, BitSet64, GrowableBitSet};
+
+  #[test]
+  fn fixed() {
+    let mut a: BitSet64 = [1, 5, 63].into_iter().collect();
+    assert_eq!(a.len(), 3);
+    assert!(a.contains(63));
+    assert!(!a.contains(64));
+    assert!(!a.insert(5));
+    assert!(a.remove(5));
+    assert_eq!(a.iter().collect::<Vec<_>>(), vec![1, 63]);
+    assert_eq!(a.first(), Some(1));
+
+    let b = BitSet64::new().with(1).with(2);
+    assert_eq!((a & b).iter().collect::<Vec<_>>(), vec![1]);
+    assert_eq!((a | b).len(), 3);
+    assert_eq!((a - b).iter().collect::<Vec<_>>(), vec![63]);
+    assert_eq!(a.symmetric_difference(&b).len(), 2);
+    assert!(BitSet64::new().with(1).is_subset(&b));
+    assert_eq!(format!("{b:?}"), "{1, 2}");
+  }
+
+  #[test]
+  fn wide() {
+    let a: BitSet128 = [0, 100, 127].into_iter().collect();
+    assert_eq!(a.iter().collect::<Vec<_>>(), vec![0, 100, 127]);
+    let b: BitSet<u32> = BitSet::from_bits(0b1010);
+    assert_eq!(b.iter().collect::<Vec<_>>(), vec![1, 3]);
+  }
+
+  #[test]
+  #[should_panic]
+  fn out_of_bounds() {
+    BitSet64::new().insert(64);
+  }
+
+  #[test]
+  fn letters() {
+    assert_eq!(letter_index('a'), Some(1));
+    assert_eq!(letter_index('Z'), Some(52));
+    assert_eq!(letter_index('-'), None);
+    for i in 1..=52 {
+      assert_eq!(letter_index(index_letter(i).unwrap()), Some(i));
+    }
+    let set = BitSet64::from_letters("vJrwpWtwJgWr");
+    assert_eq!(set.letters().collect::<String>(), "gprtvwJW");
+  }
+
+  #[test]
+  fn growable() {
+    let mut a: GrowableBitSet = [3, 70, 200].into_iter().collect();
+    assert_eq!(a.len(), 3);
+    assert!(a.contains(200));
+    assert!(!a.contains(1000));
+    let b: GrowableBitSet = [3, 500].into_iter().collect();
+    assert_eq!(a.intersection(&b).iter().collect::<Vec<_>>(), vec![3]);
+    assert_eq!(a.union(&b).len(), 4);
+    assert_eq!(a.difference(&b).iter().collect::<Vec<_>>(), vec![70, 200]);
+    assert_eq!(a.symmetric_difference(&b).len(), 3);
+    a.remove(200);
+    a.remove(70);
+    assert_eq!(a, [3].into_iter().collect());
+    assert!(a.is_subset(&b));
+  }
+}
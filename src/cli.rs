@@ -0,0 +1,104 @@
+use std::fmt::Debug;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Part {
+  One,
+  Two,
+}
+
+/// Arguments given after the day number: an optional leading command such as
+/// `render`, then `--name value` (or `--name=value`) options and `--flag`s.
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+  args: Vec<String>,
+}
+
+impl Options {
+  pub fn new(args: &[String]) -> Self {
+    Self {
+      args: args.to_vec(),
+    }
+  }
+
+  pub fn command(&self) -> Option<&str> {
+    self
+      .args
+      .first()
+      .filter(|a| !a.starts_with("--"))
+      .map(String::as_str)
+  }
+
+  pub fn value(&self, name: &str) -> Option<&str> {
+    let option = format!("--{name}");
+    let mut iter = self.args.iter();
+    while let Some(arg) = iter.next() {
+      if *arg == option {
+        return iter.next().map(String::as_str);
+      }
+      if let Some(value) = arg.strip_prefix(&option).and_then(|v| v.strip_prefix('=')) {
+        return Some(value);
+      }
+    }
+    None
+  }
+
+  /// Value of `--name` parsed as `T`. Panics with a readable message if it
+  /// does not parse.
+  pub fn parsed<T>(&self, name: &str) -> Option<T>
+  where
+    T: FromStr,
+    T::Err: Debug,
+  {
+    self.value(name).map(|v| {
+      v.parse()
+        .unwrap_or_else(|e| panic!("invalid value `{v}` for --{name}: {e:?}"))
+    })
+  }
+
+  pub fn flag(&self, name: &str) -> bool {
+    let option = format!("--{name}");
+    self.args.contains(&option)
+  }
+
+  /// `--part 1` or `--part 2`; the second part unless told otherwise.
+  pub fn part(&self) -> Part {
+    match self.value("part") {
+      None | Some("2") => Part::Two,
+      Some("1") => Part::One,
+      Some(other) => panic!("invalid value `{other}` for --part: expected 1 or 2"),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Options, Part};
+
+  fn options(args: &str) -> Options {
+    let args: Vec<String> = args.split_whitespace().map(|a| a.to_owned()).collect();
+    Options::new(&args)
+  }
+
+  #[test]
+  fn parsing() {
+    let o = options("render --top 5 --part=1 --verbose");
+    assert_eq!(o.command(), Some("render"));
+    assert_eq!(o.value("top"), Some("5"));
+    assert_eq!(o.parsed::<usize>("top"), Some(5));
+    assert_eq!(o.part(), Part::One);
+    assert!(o.flag("verbose"));
+    assert!(!o.flag("quiet"));
+    assert_eq!(o.value("missing"), None);
+
+    let o = options("--top 2");
+    assert_eq!(o.command(), None);
+    assert_eq!(o.part(), Part::Two);
+  }
+
+  #[test]
+  #[should_panic(expected = "invalid value `x` for --top")]
+  fn invalid_value() {
+    options("--top x").parsed::<usize>("top");
+  }
+}
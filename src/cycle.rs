@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The states reached after `start` steps and after `start + length` steps are
+/// the same, so everything from `start` on repeats every `length` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+  pub start: usize,
+  pub length: usize,
+}
+
+impl Cycle {
+  /// Earliest step that leaves the simulation in the same state as `step`.
+  pub fn equivalent_step(&self, step: usize) -> usize {
+    if step < self.start {
+      step
+    } else {
+      self.start + (step - self.start) % self.length
+    }
+  }
+
+  /// Value of a counter after `step` steps. `history[i]` holds the counter after
+  /// `i` steps and must reach at least `start + length`.
+  pub fn extrapolate(&self, history: &[isize], step: usize) -> isize {
+    if step < history.len() {
+      return history[step];
+    }
+    let per_cycle = history[self.start + self.length] - history[self.start];
+    let cycles = ((step - self.start) / self.length) as isize;
+    history[self.equivalent_step(step)] + cycles * per_cycle
+  }
+}
+
+/// Runs `step` on `state` until a fingerprint repeats, remembering every
+/// fingerprint seen. `state` is left at `start + length` steps.
+pub fn find_cycle<S, K, F, P>(state: &mut S, step: F, fingerprint: P) -> Cycle
+where
+  K: Eq + Hash,
+  F: FnMut(&mut S),
+  P: FnMut(&S) -> K,
+{
+  let (cycle, _) = find_cycle_with_history(state, step, fingerprint, |_| 0);
+  cycle
+}
+
+/// Same as `find_cycle`, also recording `counter` after every step so it can be
+/// fed to `Cycle::extrapolate`.
+pub fn find_cycle_with_history<S, K, F, P, C>(
+  state: &mut S,
+  mut step: F,
+  mut fingerprint: P,
+  mut counter: C,
+) -> (Cycle, Vec<isize>)
+where
+  K: Eq + Hash,
+  F: FnMut(&mut S),
+  P: FnMut(&S) -> K,
+  C: FnMut(&S) -> isize,
+{
+  let mut seen: HashMap<K, usize> = HashMap::new();
+  let mut history: Vec<isize> = vec![];
+  loop {
+    let i = history.len();
+    history.push(counter(state));
+    if let Some(start) = seen.insert(fingerprint(state), i) {
+      let cycle = Cycle {
+        start,
+        length: i - start,
+      };
+      return (cycle, history);
+    }
+    step(state);
+  }
+}
+
+/// Brent's algorithm: finds the cycle keeping only two copies of the state
+/// instead of every fingerprint seen so far.
+pub fn brent<S, K, F, P>(initial: &S, mut step: F, mut fingerprint: P) -> Cycle
+where
+  S: Clone,
+  K: Eq,
+  F: FnMut(&mut S),
+  P: FnMut(&S) -> K,
+{
+  let mut power: usize = 1;
+  let mut length: usize = 1;
+  let mut tortoise = initial.clone();
+  let mut hare = initial.clone();
+  step(&mut hare);
+  while fingerprint(&tortoise) != fingerprint(&hare) {
+    if power == length {
+      tortoise = hare.clone();
+      power *= 2;
+      length = 0;
+    }
+    step(&mut hare);
+    length += 1;
+  }
+
+  let mut tortoise = initial.clone();
+  let mut hare = initial.clone();
+  for _ in 0..length {
+    step(&mut hare);
+  }
+  let mut start: usize = 0;
+  while fingerprint(&tortoise) != fingerprint(&hare) {
+    step(&mut tortoise);
+    step(&mut hare);
+    start += 1;
+  }
+  Cycle { start, length }
+}
+
+/// Value of `counter` after `target` steps, simulating only until the first
+/// repeated fingerprint.
+pub fn extrapolate<S, K, F, P, C>(
+  mut state: S,
+  mut step: F,
+  mut fingerprint: P,
+  mut counter: C,
+  target: usize,
+) -> isize
+where
+  K: Eq + Hash,
+  F: FnMut(&mut S),
+  P: FnMut(&S) -> K,
+  C: FnMut(&S) -> isize,
+{
+  let mut seen: HashMap<K, usize> = HashMap::new();
+  let mut history: Vec<isize> = vec![];
+  loop {
+    let i = history.len();
+    history.push(counter(&state));
+    if i == target {
+      return history[i];
+    }
+    if let Some(start) = seen.insert(fingerprint(&state), i) {
+      let cycle = Cycle {
+        start,
+        length: i - start,
+      };
+      return cycle.extrapolate(&history, target);
+    }
+    step(&mut state);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{brent, extrapolate, find_cycle, find_cycle_with_history, Cycle};
+
+  const SAMPLE: Cycle = Cycle {
+    start: 2,
+    length: 3,
+  };
+
+  // 0, 1, 2, 3, 4, 2, 3, 4, ...
+  fn step(x: &mut usize) {
+    *x = if *x == 4 { 2 } else { *x + 1 };
+  }
+
+  #[test]
+  fn hashing() {
+    let mut state: usize = 0;
+    let cycle = find_cycle(&mut state, step, |&x| x);
+    assert_eq!(cycle, SAMPLE);
+    assert_eq!(state, 2);
+  }
+
+  #[test]
+  fn brent_matches_hashing() {
+    let cycle = brent(&0, step, |&x| x);
+    assert_eq!(cycle, SAMPLE);
+    assert_eq!(brent(&3, step, |&x| x), Cycle { start: 0, ..SAMPLE });
+  }
+
+  #[test]
+  fn equivalent_step() {
+    let cycle = SAMPLE;
+    assert_eq!(cycle.equivalent_step(1), 1);
+    assert_eq!(cycle.equivalent_step(5), 2);
+    assert_eq!(cycle.equivalent_step(1000), 4);
+  }
+
+  #[test]
+  fn counter() {
+    // count how many times the state has been 4
+    let mut state: (usize, isize) = (0, 0);
+    let mut step = |s: &mut (usize, isize)| {
+      step(&mut s.0);
+      if s.0 == 4 {
+        s.1 += 1;
+      }
+    };
+    let (cycle, history) = find_cycle_with_history(&mut state, &mut step, |s| s.0, |s| s.1);
+    assert_eq!(history, vec![0, 0, 0, 0, 1, 1]);
+    assert_eq!(cycle.extrapolate(&history, 4), 1);
+    assert_eq!(cycle.extrapolate(&history, 7), 2);
+    assert_eq!(cycle.extrapolate(&history, 3001), 1000);
+    assert_eq!(extrapolate((0, 0), step, |s| s.0, |s| s.1, 3001), 1000);
+  }
+}
@@ -1,5 +1,8 @@
-use std::cell::RefCell;
-use std::default;
+use crate::cli::{Options, Part};
+use crate::expr::Expr;
+use crate::numeric;
+use crate::parser::{parse_line, ParseResult, Parser};
+use crate::records;
 use std::fs::File;
 use std::io::{prelude::*, BufReader};
 
@@ -13,70 +16,34 @@ fn read_data() -> Input {
   line_iter.map(|l| l.unwrap()).collect()
 }
 
-#[derive(Debug, Clone, Copy)]
-enum OpValue {
-  Old,
-  Number(isize),
-}
-
-impl OpValue {
-  fn from_str(s: &str) -> Self {
-    match s {
-      "old" => OpValue::Old,
-      x => OpValue::Number(x.parse().unwrap()),
-    }
-  }
-
-  fn to_value(&self, old: isize) -> isize {
-    match self {
-      Self::Old => old,
-      Self::Number(n) => *n,
-    }
-  }
-}
+// `new = <expression of old>`
+#[derive(Debug, Clone)]
+struct Operation(Expr);
 
-impl Default for OpValue {
+impl Default for Operation {
   fn default() -> Self {
-    Self::Old
+    Self(Expr::Variable(OLD.to_string()))
   }
 }
 
-#[derive(Debug, Clone, Copy)]
-enum Operator {
-  Sum,
-  Mult,
-}
+const OLD: &str = "old";
 
-impl Operator {
-  fn from_str(s: &str) -> Self {
-    match s {
-      "+" => Self::Sum,
-      "*" => Self::Mult,
-      _ => unreachable!(),
-    }
-  }
-}
-
-impl Default for Operator {
-  fn default() -> Self {
-    Self::Sum
-  }
-}
-
-#[derive(Debug, Clone, Copy, Default)]
-struct Operation {
-  a: OpValue,
-  b: OpValue,
-  operator: Operator,
+/// How worry levels are kept manageable after each inspection.
+#[derive(Debug, Clone, Copy)]
+enum Relief {
+  /// Part One: the level is divided by three once the item is left undamaged.
+  DivideByThree,
+  /// Part Two: levels are only ever tested for divisibility, so they can be
+  /// kept modulo a common multiple of every divisor.
+  Modulo(isize),
 }
 
 impl Operation {
-  fn exec(&self, val: isize) -> isize {
-    let a = self.a.to_value(val);
-    let b = self.b.to_value(val);
-    match self.operator {
-      Operator::Sum => a + b,
-      Operator::Mult => a * b,
+  fn exec(&self, val: isize, relief: Relief) -> isize {
+    let vars = |name: &str| (name == OLD).then_some(val);
+    match relief {
+      Relief::DivideByThree => self.0.eval(&vars).unwrap() / 3,
+      Relief::Modulo(modulus) => self.0.eval_mod(&vars, modulus).unwrap(),
     }
   }
 }
@@ -108,124 +75,100 @@ struct Monkey {
 }
 
 impl Monkey {
-  fn from_lines(lines: Vec<String>) -> Self {
+  // Each line is recognised by its label, so their order does not matter.
+  fn parse_attribute(&mut self, p: &mut Parser) -> ParseResult<()> {
+    if p.try_keyword("Monkey") {
+      self.id = p.unsigned()?;
+      p.literal(":")?;
+    } else if p.try_literal("Starting items:") {
+      self.items = p.separated(",", Parser::signed)?;
+    } else if p.try_literal("Operation:") {
+      p.keyword("new")?;
+      p.literal("=")?;
+      self.operation = Operation(Expr::parse(p)?);
+    } else if p.try_literal("Test:") {
+      p.literal("divisible by")?;
+      self.test.test_value = p.unsigned()?;
+    } else if p.try_literal("If true:") {
+      p.literal("throw to monkey")?;
+      self.test.target_true = p.unsigned()?;
+    } else if p.try_literal("If false:") {
+      p.literal("throw to monkey")?;
+      self.test.target_false = p.unsigned()?;
+    } else {
+      return Err(p.error("expected a monkey attribute"));
+    }
+    Ok(())
+  }
+
+  fn from_lines(lines: &[String]) -> ParseResult<Self> {
     let mut instance: Self = Default::default();
     for (i, l) in lines.iter().enumerate() {
-      match i {
-        0 => {
-          let id = l
-            .strip_prefix("Monkey ")
-            .unwrap()
-            .strip_suffix(":")
-            .unwrap();
-          let id: usize = id.parse().unwrap();
-          instance.id = id;
-        }
-        1 => {
-          let items = l.trim().strip_prefix("Starting items: ").unwrap();
-          let items: Vec<isize> = items
-            .split(",")
-            .map(|item| item.trim().parse().unwrap())
-            .collect();
-          instance.items = items;
-        }
-        2 => {
-          let items = l.trim().strip_prefix("Operation: new = ").unwrap();
-          let items: Vec<&str> = items.split(" ").collect();
-          let op = Operation {
-            a: OpValue::from_str(items[0]),
-            b: OpValue::from_str(items[2]),
-            operator: Operator::from_str(items[1]),
-          };
-          instance.operation = op;
-        }
-        3 => {
-          let value: isize = l
-            .trim()
-            .strip_prefix("Test: divisible by ")
-            .unwrap()
-            .parse()
-            .unwrap();
-          instance.test.test_value = value;
-        }
-        4 => {
-          let value: usize = l
-            .trim()
-            .strip_prefix("If true: throw to monkey ")
-            .unwrap()
-            .parse()
-            .unwrap();
-          instance.test.target_true = value;
-        }
-        5 => {
-          let value: usize = l
-            .trim()
-            .strip_prefix("If false: throw to monkey ")
-            .unwrap()
-            .parse()
-            .unwrap();
-          instance.test.target_false = value;
-        }
-        _ => unreachable!(),
-      }
+      parse_line(l, |p| instance.parse_attribute(p)).map_err(|e| e.at_line(i + 1))?;
     }
-
-    instance
+    Ok(instance)
   }
 }
 
+const ITERATIONS_V1: usize = 20;
 const ITERATIONS: usize = 10000;
 
-fn solve(input: Input) -> usize {
-  let filtered: Input = input.into_iter().filter(|s| !s.is_empty()).collect();
-  let grouped: Vec<Input> = filtered.chunks(6).map(|c| c.to_owned()).collect();
-  let monkeys: Vec<RefCell<Monkey>> = grouped
-    .into_iter()
-    .map(Monkey::from_lines)
-    .map(|m| RefCell::new(m))
-    .collect();
-  let common_multiplier = monkeys
-    .iter()
-    .map(|m| m.borrow().test.test_value)
-    .fold(1, |acc, x| acc * x);
-  for _ in 0..ITERATIONS {
-    for monkey_ref in monkeys.iter() {
-      {
-        let m = monkey_ref.borrow();
-        for &item in m.items.iter() {
-          let next_worry_level = m.operation.exec(item);
-          let target = m.test.test(next_worry_level);
-          let mut target = monkeys[target].borrow_mut();
-          target.items.push(next_worry_level % common_multiplier);
-        }
-      }
-      let mut m = monkey_ref.borrow_mut();
-      let inspected = m.items.len();
-      m.items = vec![];
-      m.inspected_items += inspected;
+fn parse_monkeys(input: Input) -> Vec<Monkey> {
+  records::chunks(&input, 6)
+    .map(|record| {
+      let record = record.unwrap_or_else(|e| panic!("{e}"));
+      Monkey::from_lines(record.lines)
+        .unwrap_or_else(|e| panic!("monkey at line {}: {e}", record.line))
+    })
+    .collect()
+}
+
+fn common_modulus(monkeys: &[Monkey]) -> isize {
+  numeric::lcm_all(monkeys.iter().map(|m| m.test.test_value))
+}
+
+fn play_round(monkeys: &mut [Monkey], relief: Relief) {
+  for i in 0..monkeys.len() {
+    let items = std::mem::take(&mut monkeys[i].items);
+    monkeys[i].inspected_items += items.len();
+    for item in items {
+      let m = &monkeys[i];
+      let next_worry_level = m.operation.exec(item, relief);
+      let target = m.test.test(next_worry_level);
+      monkeys[target].items.push(next_worry_level);
     }
   }
+}
 
-  let mut monkeys: Vec<usize> = monkeys
-    .into_iter()
-    .map(|m| m.into_inner())
-    .map(|m| m.inspected_items)
-    .collect();
+fn monkey_business(monkeys: &[Monkey]) -> usize {
+  let mut inspected: Vec<usize> = monkeys.iter().map(|m| m.inspected_items).collect();
+  inspected.sort();
+  inspected.iter().rev().take(2).product()
+}
 
-  monkeys.sort();
-  let score: usize = monkeys.iter().rev().take(2).fold(1, |acc, v| acc * *v);
-  score
+fn solve(input: Input, part: Part) -> usize {
+  let mut monkeys = parse_monkeys(input);
+  let (rounds, relief) = match part {
+    Part::One => (ITERATIONS_V1, Relief::DivideByThree),
+    Part::Two => (ITERATIONS, Relief::Modulo(common_modulus(&monkeys))),
+  };
+  for _ in 0..rounds {
+    play_round(&mut monkeys, relief);
+  }
+  monkey_business(&monkeys)
 }
 
-pub fn eleven() {
+pub fn eleven(options: &Options) {
   let input = read_data();
-  let score = solve(input);
+  let score = solve(input, options.part());
   println!("{score}")
 }
 
 #[cfg(test)]
 mod tests {
-  use super::solve;
+  use super::{common_modulus, parse_monkeys, play_round, solve, Monkey, Relief, ITERATIONS};
+  use crate::cli::Part;
+  use crate::cycle;
 
   #[test]
   fn simple() {
@@ -235,11 +178,89 @@ mod tests {
       .filter(|s| !s.is_empty())
       .collect();
     println!("{input:?}");
-    let score = solve(input);
-    assert_eq!(score, 2713310158);
+    assert_eq!(solve(input.clone(), Part::One), 10605);
+    assert_eq!(solve(input, Part::Two), 2713310158);
+  }
+
+  #[test]
+  fn parse_any_order() {
+    let lines: Vec<String> = SAMPLE
+      .lines()
+      .skip(1)
+      .take(6)
+      .map(|r| r.to_owned())
+      .collect();
+    let mut shuffled = lines.clone();
+    shuffled.reverse();
+    let a = Monkey::from_lines(&lines).unwrap();
+    let b = Monkey::from_lines(&shuffled).unwrap();
+    assert_eq!(a.items, b.items);
+    assert_eq!(a.test.target_false, b.test.target_false);
+    assert_eq!(b.test.test_value, 23);
+
+    let mut broken = lines;
+    broken[2] = "  Operation: new = old * * 19".to_owned();
+    let err = Monkey::from_lines(&broken).unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "line 3, column 26: expected a number, a variable or `(`"
+    );
+  }
+
+  // Items never interact, so each one can be followed on its own: the state is
+  // the monkey holding it and its worry level at the start of a round. Returns
+  // the monkeys that inspected the item during the round.
+  fn item_round(monkeys: &[Monkey], modulus: isize, item: &mut (usize, isize)) -> Vec<usize> {
+    let mut inspected_by = vec![];
+    loop {
+      let m = &monkeys[item.0];
+      inspected_by.push(m.id);
+      let worry = m.operation.exec(item.1, Relief::Modulo(modulus));
+      let target = m.test.test(worry);
+      let same_round = target > item.0;
+      *item = (target, worry);
+      if !same_round {
+        return inspected_by;
+      }
+    }
+  }
+
+  #[test]
+  fn item_cycles() {
+    let input: Vec<String> = SAMPLE.lines().map(|r| r.to_owned()).collect();
+    let monkeys = parse_monkeys(input);
+    let modulus = common_modulus(&monkeys);
+
+    let mut expected = monkeys.clone();
+    for _ in 0..ITERATIONS {
+      play_round(&mut expected, Relief::Modulo(modulus));
+    }
+
+    let items: Vec<(usize, isize)> = monkeys
+      .iter()
+      .flat_map(|m| m.items.iter().map(move |&worry| (m.id, worry)))
+      .collect();
+    for monkey in monkeys.iter() {
+      let inspected: isize = items
+        .iter()
+        .map(|&item| {
+          cycle::extrapolate(
+            (item, 0),
+            |(item, count): &mut ((usize, isize), isize)| {
+              let inspected_by = item_round(&monkeys, modulus, item);
+              *count += inspected_by.iter().filter(|&&id| id == monkey.id).count() as isize;
+            },
+            |(item, _)| *item,
+            |(_, count)| *count,
+            ITERATIONS,
+          )
+        })
+        .sum();
+      assert_eq!(inspected as usize, expected[monkey.id].inspected_items);
+    }
   }
 
-  const SAMPLE: &'static str = r#"
+  const SAMPLE: &str = r#"
 Monkey 0:
   Starting items: 79, 98
   Operation: new = old * 19
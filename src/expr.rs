@@ -0,0 +1,507 @@
+use crate::numeric;
+use crate::parser::{parse_line, ParseError, ParseResult, Parser};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinOp {
+  Add,
+  Sub,
+  Mul,
+  Div,
+}
+
+impl BinOp {
+  fn symbol(&self) -> char {
+    match self {
+      BinOp::Add => '+',
+      BinOp::Sub => '-',
+      BinOp::Mul => '*',
+      BinOp::Div => '/',
+    }
+  }
+
+  fn apply(&self, a: isize, b: isize) -> Result<isize, EvalError> {
+    let result = match self {
+      BinOp::Add => a.checked_add(b),
+      BinOp::Sub => a.checked_sub(b),
+      BinOp::Mul => a.checked_mul(b),
+      BinOp::Div if b == 0 => return Err(EvalError::DivisionByZero),
+      BinOp::Div => a.checked_div(b),
+    };
+    result.ok_or(EvalError::Overflow)
+  }
+
+  fn apply_mod(&self, a: isize, b: isize, m: isize) -> Result<isize, EvalError> {
+    Ok(match self {
+      BinOp::Add => numeric::add_mod(a, b, m),
+      BinOp::Sub => numeric::sub_mod(a, b, m),
+      BinOp::Mul => numeric::mul_mod(a, b, m),
+      BinOp::Div if b == 0 => return Err(EvalError::DivisionByZero),
+      BinOp::Div => {
+        let inverse = numeric::mod_inverse(b, m).ok_or(EvalError::NotInvertible)?;
+        numeric::mul_mod(a, inverse, m)
+      }
+    })
+  }
+}
+
+/// Integer expression with `+ - * /`, parentheses and named variables.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Expr {
+  Number(isize),
+  Variable(String),
+  Neg(Box<Expr>),
+  Binary(Box<Expr>, BinOp, Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+  UnknownVariable(String),
+  DivisionByZero,
+  Overflow,
+  /// The unknown is multiplied by itself or ends up in a divisor.
+  NonLinear,
+  /// Every value, or no value at all, satisfies the equation.
+  NoUniqueSolution,
+  /// The only solution is not an integer.
+  NotAnInteger,
+  /// The divisor has no inverse for the modulus in use.
+  NotInvertible,
+  /// The solution relies on a division that `eval` would truncate.
+  InexactDivision,
+}
+
+impl Display for EvalError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      EvalError::UnknownVariable(name) => write!(f, "unknown variable `{name}`"),
+      EvalError::DivisionByZero => write!(f, "division by zero"),
+      EvalError::Overflow => write!(f, "integer overflow"),
+      EvalError::NonLinear => write!(f, "the equation is not linear"),
+      EvalError::NoUniqueSolution => write!(f, "the equation has no unique solution"),
+      EvalError::NotAnInteger => write!(f, "the solution is not an integer"),
+      EvalError::NotInvertible => write!(f, "the divisor has no modular inverse"),
+      EvalError::InexactDivision => write!(f, "the solution needs an inexact division"),
+    }
+  }
+}
+
+impl std::error::Error for EvalError {}
+
+impl Expr {
+  pub fn binary(a: Expr, op: BinOp, b: Expr) -> Expr {
+    Expr::Binary(Box::new(a), op, Box::new(b))
+  }
+
+  /// Reads an expression from the cursor, stopping at the first token that
+  /// cannot continue it.
+  pub fn parse(p: &mut Parser) -> ParseResult<Expr> {
+    let mut expr = Self::parse_term(p)?;
+    while let Ok(op) = p.one_of(&[("+", BinOp::Add), ("-", BinOp::Sub)]) {
+      expr = Expr::binary(expr, op, Self::parse_term(p)?);
+    }
+    Ok(expr)
+  }
+
+  fn parse_term(p: &mut Parser) -> ParseResult<Expr> {
+    let mut expr = Self::parse_factor(p)?;
+    while let Ok(op) = p.one_of(&[("*", BinOp::Mul), ("/", BinOp::Div)]) {
+      expr = Expr::binary(expr, op, Self::parse_factor(p)?);
+    }
+    Ok(expr)
+  }
+
+  fn parse_factor(p: &mut Parser) -> ParseResult<Expr> {
+    if p.try_literal("-") {
+      return Ok(Expr::Neg(Box::new(Self::parse_factor(p)?)));
+    }
+    if p.try_literal("(") {
+      let expr = Self::parse(p)?;
+      p.literal(")")?;
+      return Ok(expr);
+    }
+    match p.peek() {
+      Some(c) if c.is_ascii_digit() => Ok(Expr::Number(p.unsigned()?)),
+      Some(c) if c.is_ascii_alphabetic() => Ok(Expr::Variable(p.word()?.to_string())),
+      _ => Err(p.error("expected a number, a variable or `(`")),
+    }
+  }
+
+  pub fn eval(&self, vars: &impl Fn(&str) -> Option<isize>) -> Result<isize, EvalError> {
+    match self {
+      Expr::Number(n) => Ok(*n),
+      Expr::Variable(name) => vars(name).ok_or_else(|| EvalError::UnknownVariable(name.clone())),
+      Expr::Neg(e) => e.eval(vars)?.checked_neg().ok_or(EvalError::Overflow),
+      Expr::Binary(a, op, b) => op.apply(a.eval(vars)?, b.eval(vars)?),
+    }
+  }
+
+  /// Value modulo `m`, in `0..m`. Every intermediate result is reduced, so
+  /// nothing overflows however large the operands grow.
+  ///
+  /// This is field arithmetic: `a / b` is `a` times the inverse of `b`, so it
+  /// only agrees with `eval` when every division is exact. A divisor that is
+  /// a multiple of `m` is a division by zero.
+  pub fn eval_mod(
+    &self,
+    vars: &impl Fn(&str) -> Option<isize>,
+    m: isize,
+  ) -> Result<isize, EvalError> {
+    match self {
+      Expr::Number(n) => Ok(numeric::modulo(*n, m)),
+      Expr::Variable(name) => vars(name)
+        .map(|v| numeric::modulo(v, m))
+        .ok_or_else(|| EvalError::UnknownVariable(name.clone())),
+      Expr::Neg(e) => Ok(numeric::sub_mod(0, e.eval_mod(vars, m)?, m)),
+      Expr::Binary(a, op, b) => op.apply_mod(a.eval_mod(vars, m)?, b.eval_mod(vars, m)?, m),
+    }
+  }
+
+  /// Replaces every variable `lookup` knows about by its definition,
+  /// recursively, so chains of named sub-expressions collapse into one tree.
+  pub fn inline<'a>(&self, lookup: &impl Fn(&str) -> Option<&'a Expr>) -> Expr {
+    match self {
+      Expr::Variable(name) => match lookup(name) {
+        Some(definition) => definition.inline(lookup),
+        None => self.clone(),
+      },
+      Expr::Neg(e) => Expr::Neg(Box::new(e.inline(lookup))),
+      Expr::Binary(a, op, b) => Expr::binary(a.inline(lookup), *op, b.inline(lookup)),
+      Expr::Number(_) => self.clone(),
+    }
+  }
+
+  fn linear(
+    &self,
+    unknown: &str,
+    vars: &impl Fn(&str) -> Option<isize>,
+  ) -> Result<Linear, EvalError> {
+    match self {
+      Expr::Variable(name) if name == unknown => Ok(Linear {
+        coefficient: Ratio::from(1),
+        constant: Ratio::from(0),
+      }),
+      Expr::Number(_) | Expr::Variable(_) => Ok(Linear::constant(Ratio::from(self.eval(vars)?))),
+      Expr::Neg(e) => e.linear(unknown, vars)?.scale(Ratio::from(-1)),
+      Expr::Binary(a, op, b) => {
+        let a = a.linear(unknown, vars)?;
+        let b = b.linear(unknown, vars)?;
+        match op {
+          BinOp::Add => a.add(&b),
+          BinOp::Sub => a.add(&b.scale(Ratio::from(-1))?),
+          BinOp::Mul if a.is_constant() => b.scale(a.constant),
+          BinOp::Mul if b.is_constant() => a.scale(b.constant),
+          BinOp::Div if b.is_constant() && a.is_constant() => {
+            // keep integer division semantics when nothing depends on the unknown
+            let divided = BinOp::Div.apply(a.constant.to_integer()?, b.constant.to_integer()?)?;
+            Ok(Linear::constant(Ratio::from(divided)))
+          }
+          BinOp::Div if b.is_constant() => a.scale(b.constant.inverse()?),
+          _ => Err(EvalError::NonLinear),
+        }
+      }
+    }
+  }
+
+  /// Value of `unknown` that makes `self == rhs`, every other variable being
+  /// taken from `vars`. Both sides must be linear in `unknown`, and the
+  /// answer must hold with the truncating divisions of `eval`.
+  pub fn solve_for(
+    &self,
+    rhs: &Expr,
+    unknown: &str,
+    vars: &impl Fn(&str) -> Option<isize>,
+  ) -> Result<isize, EvalError> {
+    let left = self.linear(unknown, vars)?;
+    let right = rhs.linear(unknown, vars)?;
+    // (a - c) * x = d - b
+    let coefficient = left.coefficient.sub(&right.coefficient)?;
+    if coefficient.is_zero() {
+      return Err(EvalError::NoUniqueSolution);
+    }
+    let value = right.constant.sub(&left.constant)?.div(&coefficient)?;
+    let value = value.to_integer().map_err(|_| EvalError::NotAnInteger)?;
+    // `linear` divides exactly while `eval` truncates, so check the answer
+    let with_value = |name: &str| {
+      if name == unknown {
+        Some(value)
+      } else {
+        vars(name)
+      }
+    };
+    if self.eval(&with_value)? != rhs.eval(&with_value)? {
+      return Err(EvalError::InexactDivision);
+    }
+    Ok(value)
+  }
+}
+
+impl FromStr for Expr {
+  type Err = ParseError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    parse_line(s, Expr::parse)
+  }
+}
+
+impl Display for Expr {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Expr::Number(n) => write!(f, "{n}"),
+      Expr::Variable(name) => write!(f, "{name}"),
+      Expr::Neg(e) => write!(f, "-{e}"),
+      Expr::Binary(a, op, b) => write!(f, "({a} {} {b})", op.symbol()),
+    }
+  }
+}
+
+/// Exact fraction, always stored with a positive denominator and in lowest
+/// terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Ratio {
+  num: i128,
+  den: i128,
+}
+
+impl From<isize> for Ratio {
+  fn from(n: isize) -> Self {
+    Ratio {
+      num: n as i128,
+      den: 1,
+    }
+  }
+}
+
+impl Ratio {
+  fn new(num: i128, den: i128) -> Result<Self, EvalError> {
+    if den == 0 {
+      return Err(EvalError::DivisionByZero);
+    }
+    let g = numeric::gcd_i128(num, den) * den.signum();
+    Ok(Ratio {
+      num: num / g,
+      den: den / g,
+    })
+  }
+
+  fn is_zero(&self) -> bool {
+    self.num == 0
+  }
+
+  fn add(&self, other: &Self) -> Result<Self, EvalError> {
+    let num = self
+      .num
+      .checked_mul(other.den)
+      .zip(other.num.checked_mul(self.den))
+      .and_then(|(a, b)| a.checked_add(b));
+    let den = self.den.checked_mul(other.den);
+    Ratio::new(
+      num.ok_or(EvalError::Overflow)?,
+      den.ok_or(EvalError::Overflow)?,
+    )
+  }
+
+  fn sub(&self, other: &Self) -> Result<Self, EvalError> {
+    self.add(&Ratio {
+      num: -other.num,
+      den: other.den,
+    })
+  }
+
+  fn mul(&self, other: &Self) -> Result<Self, EvalError> {
+    let num = self.num.checked_mul(other.num).ok_or(EvalError::Overflow)?;
+    let den = self.den.checked_mul(other.den).ok_or(EvalError::Overflow)?;
+    Ratio::new(num, den)
+  }
+
+  fn inverse(&self) -> Result<Self, EvalError> {
+    Ratio::new(self.den, self.num)
+  }
+
+  fn div(&self, other: &Self) -> Result<Self, EvalError> {
+    self.mul(&other.inverse()?)
+  }
+
+  fn to_integer(self) -> Result<isize, EvalError> {
+    if self.den != 1 {
+      return Err(EvalError::NotAnInteger);
+    }
+    self.num.try_into().map_err(|_| EvalError::Overflow)
+  }
+}
+
+/// `coefficient * unknown + constant`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Linear {
+  coefficient: Ratio,
+  constant: Ratio,
+}
+
+impl Linear {
+  fn constant(constant: Ratio) -> Self {
+    Linear {
+      coefficient: Ratio::from(0),
+      constant,
+    }
+  }
+
+  fn is_constant(&self) -> bool {
+    self.coefficient.is_zero()
+  }
+
+  fn add(&self, other: &Self) -> Result<Self, EvalError> {
+    Ok(Linear {
+      coefficient: self.coefficient.add(&other.coefficient)?,
+      constant: self.constant.add(&other.constant)?,
+    })
+  }
+
+  fn scale(&self, factor: Ratio) -> Result<Self, EvalError> {
+    Ok(Linear {
+      coefficient: self.coefficient.mul(&factor)?,
+      constant: self.constant.mul(&factor)?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{EvalError, Expr};
+  use std::collections::HashMap;
+
+  fn no_vars(_: &str) -> Option<isize> {
+    None
+  }
+
+  fn parse(s: &str) -> Expr {
+    s.parse().unwrap()
+  }
+
+  #[test]
+  fn precedence() {
+    assert_eq!(parse("1 + 2 * 3 - 4").to_string(), "((1 + (2 * 3)) - 4)");
+    assert_eq!(parse("(1 + 2) * -3").to_string(), "((1 + 2) * -3)");
+    assert_eq!(parse("8 / 2 / 2").eval(&no_vars), Ok(2));
+    assert_eq!(parse("7 - -3 * (2 + 1)").eval(&no_vars), Ok(16));
+  }
+
+  #[test]
+  fn variables() {
+    let e = parse("old * old + x");
+    let vars = |name: &str| match name {
+      "old" => Some(5),
+      "x" => Some(1),
+      _ => None,
+    };
+    assert_eq!(e.eval(&vars), Ok(26));
+    assert_eq!(
+      parse("y + 1").eval(&vars),
+      Err(EvalError::UnknownVariable("y".to_string()))
+    );
+  }
+
+  #[test]
+  fn errors() {
+    assert_eq!(
+      parse("1 / (2 - 2)").eval(&no_vars),
+      Err(EvalError::DivisionByZero)
+    );
+    let big = format!("{} * 2", isize::MAX);
+    assert_eq!(parse(&big).eval(&no_vars), Err(EvalError::Overflow));
+    let err = "1 + * 2".parse::<Expr>().unwrap_err();
+    assert_eq!(err.column, 5);
+    assert!("(1 + 2".parse::<Expr>().is_err());
+  }
+
+  #[test]
+  fn modular() {
+    let vars = |name: &str| (name == "old").then_some(isize::MAX - 1);
+    assert_eq!(parse("old * old").eval_mod(&vars, isize::MAX), Ok(1));
+    assert_eq!(parse("-old + 3").eval_mod(&vars, isize::MAX), Ok(4));
+    assert_eq!(parse("1 / 3").eval_mod(&no_vars, 11), Ok(4));
+    assert_eq!(
+      parse("1 / 2").eval_mod(&no_vars, 8),
+      Err(EvalError::NotInvertible)
+    );
+    assert_eq!(
+      parse("1 / 11").eval_mod(&no_vars, 11),
+      Err(EvalError::DivisionByZero)
+    );
+    // 2 * 9 = 7 (mod 11), unlike the truncated 7 / 2 = 3
+    let inexact = parse("7 / 2");
+    assert_eq!(inexact.eval_mod(&no_vars, 11), Ok(9));
+    assert_eq!(inexact.eval(&no_vars), Ok(3));
+    assert_eq!(parse("8 / 2").eval_mod(&no_vars, 11), Ok(4));
+  }
+
+  #[test]
+  fn solve() {
+    let vars = |name: &str| (name == "k").then_some(3);
+    let lhs = parse("(4 + 2 * (x - k)) / 4");
+    assert_eq!(lhs.solve_for(&parse("4"), "x", &vars), Ok(9));
+    assert_eq!(parse("x / 2").solve_for(&parse("3"), "x", &vars), Ok(6));
+    assert_eq!(parse("2 * x").solve_for(&parse("x + 5"), "x", &vars), Ok(5));
+    assert_eq!(
+      parse("x * x").solve_for(&parse("4"), "x", &vars),
+      Err(EvalError::NonLinear)
+    );
+    assert_eq!(
+      parse("2 * x").solve_for(&parse("3"), "x", &vars),
+      Err(EvalError::NotAnInteger)
+    );
+    assert_eq!(
+      parse("x + 1").solve_for(&parse("x"), "x", &vars),
+      Err(EvalError::NoUniqueSolution)
+    );
+    // exactly x = 3, but 3 / 2 + 3 / 2 evaluates to 2
+    assert_eq!(
+      parse("x / 2 + x / 2").solve_for(&parse("3"), "x", &vars),
+      Err(EvalError::InexactDivision)
+    );
+  }
+
+  #[test]
+  fn monkey_math() {
+    let lines = [
+      "root: pppw + sjmn",
+      "dbpl: 5",
+      "cczh: sllz + lgvd",
+      "zczc: 2",
+      "ptdq: humn - dvpt",
+      "dvpt: 3",
+      "lfqf: 4",
+      "humn: 5",
+      "ljgn: 2",
+      "sjmn: drzm * dbpl",
+      "sllz: 4",
+      "pppw: cczh / lfqf",
+      "lgvd: ljgn * ptdq",
+      "drzm: hmdt - zczc",
+      "hmdt: 32",
+    ];
+    let monkeys: HashMap<&str, Expr> = lines
+      .iter()
+      .map(|l| {
+        let (name, expr) = l.split_once(": ").unwrap();
+        (name, parse(expr))
+      })
+      .collect();
+    let root = Expr::Variable("root".to_string()).inline(&|name| monkeys.get(name));
+    assert_eq!(root.eval(&no_vars), Ok(152));
+
+    let lookup = |name: &str| {
+      if name == "humn" {
+        None
+      } else {
+        monkeys.get(name)
+      }
+    };
+    let Expr::Binary(lhs, _, rhs) = &monkeys["root"] else {
+      unreachable!()
+    };
+    let lhs = lhs.inline(&lookup);
+    let rhs = rhs.inline(&lookup);
+    assert_eq!(lhs.solve_for(&rhs, "humn", &no_vars), Ok(301));
+  }
+}
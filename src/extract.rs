@@ -0,0 +1,134 @@
+/// Every signed integer found in a line, left to right, without allocating.
+/// A `-` only counts as a sign when a digit follows it right away. Runs of
+/// digits too long for an `isize`, such as 20-digit ids, are skipped.
+#[derive(Debug, Clone)]
+pub struct Integers<'a> {
+  bytes: &'a [u8],
+  pos: usize,
+}
+
+pub fn integers(line: &str) -> Integers<'_> {
+  Integers {
+    bytes: line.as_bytes(),
+    pos: 0,
+  }
+}
+
+impl Iterator for Integers<'_> {
+  type Item = isize;
+
+  fn next(&mut self) -> Option<isize> {
+    let bytes = self.bytes;
+    loop {
+      while self.pos < bytes.len() && !bytes[self.pos].is_ascii_digit() {
+        self.pos += 1;
+      }
+      if self.pos == bytes.len() {
+        return None;
+      }
+      let negative = self.pos > 0 && bytes[self.pos - 1] == b'-';
+      // `None` once the run no longer fits
+      let mut value: Option<isize> = Some(0);
+      while self.pos < bytes.len() && bytes[self.pos].is_ascii_digit() {
+        let digit = (bytes[self.pos] - b'0') as isize;
+        value = value
+          .and_then(|v| v.checked_mul(10))
+          .and_then(|v| v.checked_add(digit));
+        self.pos += 1;
+      }
+      if let Some(value) = value {
+        return Some(if negative { -value } else { value });
+      }
+    }
+  }
+}
+
+/// Every run of letters, digits and underscores in a line, left to right.
+#[derive(Debug, Clone)]
+pub struct Words<'a> {
+  rest: &'a str,
+}
+
+pub fn words(line: &str) -> Words<'_> {
+  Words { rest: line }
+}
+
+fn is_word_byte(b: u8) -> bool {
+  b.is_ascii_alphanumeric() || b == b'_'
+}
+
+impl<'a> Iterator for Words<'a> {
+  type Item = &'a str;
+
+  fn next(&mut self) -> Option<&'a str> {
+    let bytes = self.rest.as_bytes();
+    let start = bytes.iter().position(|&b| is_word_byte(b))?;
+    let len = bytes[start..]
+      .iter()
+      .position(|&b| !is_word_byte(b))
+      .unwrap_or(bytes.len() - start);
+    let word = &self.rest[start..start + len];
+    self.rest = &self.rest[start + len..];
+    Some(word)
+  }
+}
+
+/// Fills `out` with the first integers of `line`. Returns `None` unless the
+/// line holds exactly `N` of them.
+pub fn exact_integers<const N: usize>(line: &str) -> Option<[isize; N]> {
+  let mut out = [0; N];
+  let mut iter = integers(line);
+  for slot in out.iter_mut() {
+    *slot = iter.next()?;
+  }
+  match iter.next() {
+    Some(_) => None,
+    None => Some(out),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{exact_integers, integers, words};
+
+  #[test]
+  fn signed_integers() {
+    let line = "Sensor at x=2, y=-18: closest beacon is at x=-2, y=15";
+    let found: Vec<isize> = integers(line).collect();
+    assert_eq!(found, vec![2, -18, -2, 15]);
+    assert_eq!(exact_integers::<4>(line), Some([2, -18, -2, 15]));
+    assert_eq!(exact_integers::<3>(line), None);
+    assert_eq!(exact_integers::<5>(line), None);
+  }
+
+  #[test]
+  fn dash_is_not_always_a_sign() {
+    let found: Vec<isize> = integers("2-4,6-8 - 3").collect();
+    assert_eq!(found, vec![2, -4, 6, -8, 3]);
+    assert_eq!(integers("no numbers - here").next(), None);
+  }
+
+  #[test]
+  fn oversized_runs_are_skipped() {
+    let line = "id 12345678901234567890 at -99999999999999999999, x=7";
+    let found: Vec<isize> = integers(line).collect();
+    assert_eq!(found, vec![7]);
+    let max = format!("{} {}", isize::MAX, -isize::MAX);
+    let found: Vec<isize> = integers(&max).collect();
+    assert_eq!(found, vec![isize::MAX, -isize::MAX]);
+  }
+
+  #[test]
+  fn identifier_words() {
+    let line = "Valve AA has flow rate=0; tunnels lead to valves DD, II, BB";
+    let found: Vec<&str> = words(line).collect();
+    assert_eq!(
+      found,
+      vec![
+        "Valve", "AA", "has", "flow", "rate", "0", "tunnels", "lead", "to", "valves", "DD", "II",
+        "BB"
+      ]
+    );
+    assert_eq!(words("  ").next(), None);
+  }
+}
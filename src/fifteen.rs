@@ -1,3 +1,5 @@
+use crate::extract::exact_integers;
+use crate::range::{Range, RangeSet};
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::collections::HashSet;
@@ -49,63 +51,6 @@ impl Point {
   }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-struct Range {
-  min: isize,
-  max: isize,
-}
-
-impl Range {
-  fn new(min: isize, max: isize) -> Range {
-    Range { min, max }
-  }
-
-  fn new_centered(center: isize, radius: isize) -> Range {
-    Range {
-      min: center - radius,
-      max: center + radius,
-    }
-  }
-
-  fn overlaps(&self, other: &Self) -> bool {
-    other.min <= self.max && other.max >= self.min
-  }
-
-  fn contains(&self, x: isize) -> bool {
-    x >= self.min && x <= self.max
-  }
-
-  fn len(&self) -> usize {
-    (self.max - self.min + 1) as usize
-  }
-
-  fn merge(&self, other: &Self) -> Option<Self> {
-    if self.overlaps(other) {
-      Some(Self {
-        min: self.min.min(other.min),
-        max: self.max.max(other.max),
-      })
-    } else {
-      None
-    }
-  }
-
-  fn into_iter(self) -> impl Iterator<Item = isize> {
-    self.min..=self.max
-  }
-
-  fn intersection(&self, bound: Self) -> Option<Self> {
-    if self.overlaps(&bound) {
-      Some(Self {
-        min: self.min.max(bound.min),
-        max: self.max.min(bound.max),
-      })
-    } else {
-      None
-    }
-  }
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 struct Sensor {
   pos: Point,
@@ -114,6 +59,24 @@ struct Sensor {
 
 impl Sensor {
   fn from_str(s: &str) -> Self {
+    Self::from_str_fast(s).unwrap_or_else(|| Self::from_str_regex(s))
+  }
+
+  fn from_str_fast(s: &str) -> Option<Self> {
+    if !s.starts_with("Sensor at") {
+      return None;
+    }
+    let [x, y, beacon_x, beacon_y] = exact_integers::<4>(s)?;
+    Some(Sensor {
+      pos: Point { x, y },
+      beacon: Point {
+        x: beacon_x,
+        y: beacon_y,
+      },
+    })
+  }
+
+  fn from_str_regex(s: &str) -> Self {
     lazy_static! {
       static ref RE: Regex = Regex::new(
         r"^Sensor at x=(\-?\d+), y=(\-?\d+): closest beacon is at x=(\-?\d+), y=(\-?\d+)$"
@@ -158,34 +121,16 @@ fn solve(input: Input, line: isize) -> usize {
 
   let known_beacons: HashSet<Point> = sensors.iter().map(|s| s.beacon).collect();
 
-  let mut blocked_ranges: Vec<Range> = sensors
+  let blocked: RangeSet = sensors
     .iter()
     .filter_map(|s| s.blocked_cells_in_line(line))
     .collect();
-  blocked_ranges.sort_by_key(|r| r.min);
-  let mut simplified_ranges: Vec<Range> = blocked_ranges.into_iter().fold(vec![], |mut acc, r| {
-    if let Some(last) = acc.last_mut() {
-      if let Some(merged) = last.merge(&r) {
-        *last = merged;
-        return acc;
-      }
-    }
-    acc.push(r);
-    acc
-  });
-  let mut blocked_cells = simplified_ranges.iter().fold(0, |acc, r| acc + r.len());
 
-  let mut used_by_sensors: usize = known_beacons
+  let used_by_beacons = known_beacons
     .iter()
-    .filter_map(|beacon| {
-      if beacon.y != line {
-        return None;
-      }
-      let x = beacon.x;
-      simplified_ranges.iter().find(|r| r.contains(x)).map(|_| 1)
-    })
-    .sum();
-  blocked_cells - used_by_sensors
+    .filter(|beacon| beacon.y == line && blocked.contains(beacon.x))
+    .count();
+  blocked.len() - used_by_beacons
 }
 
 fn solve_v2(input: Input, valid_range: Range) -> usize {
@@ -194,47 +139,17 @@ fn solve_v2(input: Input, valid_range: Range) -> usize {
 
   let mut position = Point::default();
 
-  for line in valid_range.into_iter() {
-    let mut blocked_ranges: Vec<Range> = sensors
+  for line in valid_range {
+    let blocked: RangeSet = sensors
       .iter()
-      .filter_map(|s| {
-        let calculated = s.blocked_cells_in_line(line);
-        if let Some(r) = calculated {
-          r.intersection(valid_range)
-        } else {
-          None
-        }
-      })
+      .filter_map(|s| s.blocked_cells_in_line(line))
       .collect();
-    blocked_ranges.sort_by_key(|r| r.min);
-    let mut simplified_ranges: Vec<Range> =
-      blocked_ranges.into_iter().fold(vec![], |mut acc, r| {
-        if let Some(last) = acc.last_mut() {
-          if let Some(merged) = last.merge(&r) {
-            *last = merged;
-            return acc;
-          }
-        }
-        acc.push(r);
-        acc
-      });
-    if simplified_ranges.len() == 1 {
-      let r = simplified_ranges[0];
-      if r.len() == valid_range.len() {
-        continue;
-      } else {
-        let x = if r.contains(valid_range.min) {
-          r.min
-        } else {
-          r.max
-        };
-        position = Point { x, y: line };
-        break;
-      }
-    } else {
-      let r = simplified_ranges[0];
-      let x = r.max + 1;
-      position = Point { x, y: line };
+    let first_gap = blocked.gaps_within(valid_range).next();
+    if let Some(gap) = first_gap {
+      position = Point {
+        x: gap.min,
+        y: line,
+      };
       break;
     }
   }
@@ -255,6 +170,7 @@ pub fn fifteen() {
 mod tests {
   use super::{Point, Range, Sensor};
   use indoc::indoc;
+  use std::time::Instant;
 
   #[test]
   fn parse() {
@@ -263,6 +179,35 @@ mod tests {
     assert_eq!(sensor, Sensor::default());
   }
 
+  #[test]
+  fn parse_fast_path() {
+    for line in SAMPLE.lines() {
+      let fast = Sensor::from_str_fast(line);
+      assert_eq!(fast, Some(Sensor::from_str_regex(line)));
+    }
+    assert_eq!(Sensor::from_str_fast("Sensor at x=1, y=2"), None);
+  }
+
+  // cargo test --release -- --ignored --nocapture bench
+  #[test]
+  #[ignore]
+  fn bench_parse() {
+    let lines: Vec<&str> = SAMPLE.lines().collect();
+    let rounds = 20000;
+    let time = |f: &dyn Fn(&str) -> Sensor| {
+      let start = Instant::now();
+      let mut total = 0;
+      for _ in 0..rounds {
+        total += lines.iter().map(|l| f(l).pos.x).sum::<isize>();
+      }
+      (start.elapsed(), total)
+    };
+    let (regex, a) = time(&Sensor::from_str_regex);
+    let (fast, b) = time(&|l| Sensor::from_str_fast(l).unwrap());
+    assert_eq!(a, b);
+    println!("sensors: regex {regex:?}, fast {fast:?}");
+  }
+
   #[test]
   fn blocked() {
     let input = "Sensor at x=3, y=3: closest beacon is at x=0, y=0";
@@ -271,14 +216,6 @@ mod tests {
     assert_eq!(blocked, Some(Range::new(0, 6)));
   }
 
-  #[test]
-  fn join() {
-    let r1 = Range::new(0, 6);
-    let r2 = Range::new(3, 9);
-    let r3 = Range::new(0, 9);
-    assert_eq!(r1.merge(&r2), Some(r3));
-  }
-
   #[test]
   fn solve() {
     let input = SAMPLE.lines().map(|s| s.to_string()).collect();
@@ -1,66 +1,364 @@
+use crate::cli::{Options, Part};
+use crate::parser::{parse_line, ParseResult, Parser};
+use crate::records::{self, Record, RecordError};
 use std::fs::File;
 use std::io::{prelude::*, BufReader};
 
 struct Movement {
+  line: usize,
+  from: String,
+  to: String,
+  count: usize,
+}
+
+impl Movement {
+  fn parse(p: &mut Parser, line: usize) -> ParseResult<Self> {
+    p.keyword("move")?;
+    let count: usize = p.unsigned()?;
+    p.keyword("from")?;
+    let from = p.token()?.to_string();
+    p.keyword("to")?;
+    let to = p.token()?.to_string();
+    Ok(Movement {
+      line,
+      from,
+      to,
+      count,
+    })
+  }
+
+  fn from_line(l: &str, line: usize) -> Self {
+    parse_line(l, |p| Movement::parse(p, line)).unwrap_or_else(|e| panic!("{}", e.at_line(line)))
+  }
+}
+
+// A movement between stacks known to exist and hold enough crates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Step {
   from: usize,
   to: usize,
   count: usize,
 }
 
-impl Movement {
-  fn from_line(l: &String) -> Self {
-    let words: Vec<_> = l.split_whitespace().collect();
-    let numbers: Vec<usize> = words
+/// Stacks of crates, bottom first, in the order of the labels under them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Stacks {
+  labels: Vec<String>,
+  crates: Vec<Vec<char>>,
+}
+
+impl Stacks {
+  /// Reads a drawing made of `[X]` crates above a footer row of labels. Each
+  /// crate belongs to the label its brackets sit over.
+  fn parse(drawing: Record) -> Result<Self, RecordError> {
+    let error = |line: usize, message: String| RecordError { line, message };
+    let (footer, rows) = drawing.lines.split_last().unwrap();
+    let footer_line = drawing.line + rows.len();
+    // label and the columns it spans, counted in chars like the crate rows
+    let mut labels: Vec<(String, usize, usize)> = vec![];
+    let footer_chars: Vec<char> = footer.chars().collect();
+    let mut column = 0;
+    while column < footer_chars.len() {
+      if footer_chars[column].is_whitespace() {
+        column += 1;
+        continue;
+      }
+      let start = column;
+      while column < footer_chars.len() && !footer_chars[column].is_whitespace() {
+        column += 1;
+      }
+      let label: String = footer_chars[start..column].iter().collect();
+      if labels.iter().any(|(l, _, _)| *l == label) {
+        return Err(error(footer_line, format!("label `{label}` is used twice")));
+      }
+      labels.push((label, start, column - 1));
+    }
+    if labels.is_empty() {
+      return Err(error(footer_line, "no stack labels".to_string()));
+    }
+
+    let mut crates: Vec<Vec<char>> = vec![vec![]; labels.len()];
+    // walk up from the bottom row so that stacks grow in order
+    for (i, row) in rows.iter().enumerate().rev() {
+      let line = drawing.line + i;
+      let chars: Vec<char> = row.chars().collect();
+      let mut column = 0;
+      let mut seen = vec![false; labels.len()];
+      while column < chars.len() {
+        if chars[column] == ' ' {
+          column += 1;
+          continue;
+        }
+        let (Some(&'['), Some(&c), Some(&']')) = (
+          chars.get(column),
+          chars.get(column + 1),
+          chars.get(column + 2),
+        ) else {
+          return Err(error(
+            line,
+            format!("expected `[X]` at column {}", column + 1),
+          ));
+        };
+        if c == ' ' {
+          return Err(error(line, format!("empty crate at column {}", column + 1)));
+        }
+        let Some(stack) = labels
+          .iter()
+          .position(|&(_, start, end)| start <= column + 2 && column <= end)
+        else {
+          return Err(error(
+            line,
+            format!("crate at column {} is above no label", column + 1),
+          ));
+        };
+        let label = &labels[stack].0;
+        if seen[stack] {
+          return Err(error(line, format!("two crates above label `{label}`")));
+        }
+        if crates[stack].len() != rows.len() - 1 - i {
+          return Err(error(
+            line,
+            format!("crate `{c}` floats above stack `{label}`"),
+          ));
+        }
+        seen[stack] = true;
+        crates[stack].push(c);
+        column += 3;
+      }
+    }
+    Ok(Self {
+      labels: labels.into_iter().map(|(l, _, _)| l).collect(),
+      crates,
+    })
+  }
+
+  fn index(&self, label: &str) -> Option<usize> {
+    self.labels.iter().position(|l| l == label)
+  }
+
+  /// Checks every movement against the stack heights it will meet, before
+  /// anything moves.
+  fn plan(&self, movements: &[Movement]) -> Result<Vec<Step>, RecordError> {
+    let mut heights: Vec<usize> = self.crates.iter().map(|s| s.len()).collect();
+    movements
       .iter()
-      .enumerate()
-      .filter(|(i, _)| i % 2 == 1)
-      .map(|(_, v)| v.parse().unwrap())
-      .collect();
-    Movement {
-      from: numbers[1] - 1,
-      to: numbers[2] - 1,
-      count: numbers[0],
+      .map(|m| {
+        let error = |message: String| RecordError {
+          line: m.line,
+          message,
+        };
+        let stack = |label: &str| {
+          self
+            .index(label)
+            .ok_or_else(|| error(format!("no stack labelled `{label}`")))
+        };
+        let (from, to) = (stack(&m.from)?, stack(&m.to)?);
+        if heights[from] < m.count {
+          return Err(error(format!(
+            "cannot take {} crates from stack `{}` holding {}",
+            m.count, m.from, heights[from]
+          )));
+        }
+        heights[from] -= m.count;
+        heights[to] += m.count;
+        Ok(Step {
+          from,
+          to,
+          count: m.count,
+        })
+      })
+      .collect()
+  }
+
+  // Empty stacks show as a space.
+  fn tops(&self) -> String {
+    self
+      .crates
+      .iter()
+      .map(|s| s.last().copied().unwrap_or(' '))
+      .collect()
+  }
+}
+
+/// How a crane model carries out a step.
+trait Crane {
+  fn apply(&self, stacks: &mut Stacks, step: Step);
+}
+
+/// Moves crates one at a time, so they land in reverse order.
+struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+  fn apply(&self, stacks: &mut Stacks, step: Step) {
+    for _ in 0..step.count {
+      let c = stacks.crates[step.from].pop().unwrap();
+      stacks.crates[step.to].push(c);
     }
   }
 }
 
-fn read_data() -> Vec<Movement> {
+/// Moves all the crates of a step at once, keeping their order.
+struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+  fn apply(&self, stacks: &mut Stacks, step: Step) {
+    let from = &mut stacks.crates[step.from];
+    let start = from.len() - step.count;
+    let mut dropped: Vec<char> = from.drain(start..).collect();
+    stacks.crates[step.to].append(&mut dropped);
+  }
+}
+
+type Input = Vec<String>;
+
+fn read_data() -> Input {
   let filename = format!("./resources/5.txt");
   let file: File = File::open(&filename).expect(&format!("Cannot open file {}", &filename));
   let reader = BufReader::new(file);
   let line_iter = reader.lines();
-  line_iter
-    .map(|l| l.unwrap())
-    .filter(|l| l.starts_with('m'))
-    .map(|m| Movement::from_line(&m))
-    .collect()
+  line_iter.map(|l| l.unwrap()).collect()
 }
 
-pub fn five() {
-  let mut data = vec![
-    vec!['Q', 'M', 'G', 'C', 'L'],
-    vec!['R', 'D', 'L', 'C', 'T', 'F', 'H', 'G'],
-    vec!['V', 'J', 'F', 'N', 'M', 'T', 'W', 'R'],
-    vec!['J', 'F', 'D', 'V', 'Q', 'P'],
-    vec!['N', 'F', 'M', 'S', 'L', 'B', 'T'],
-    vec!['R', 'N', 'V', 'H', 'C', 'D', 'P'],
-    vec!['H', 'C', 'T'],
-    vec!['G', 'S', 'J', 'V', 'Z', 'N', 'H', 'P'],
-    vec!['Z', 'F', 'H', 'G'],
-  ];
-  let movements = read_data();
-  for m in movements {
-    let from: &mut Vec<_> = data.get_mut(m.from).unwrap();
-    let end = from.len();
-    let start = end - m.count;
-    let mut dropped: Vec<char> = from.drain(start..end).collect();
-    //dropped.reverse();
-    let to: &mut Vec<_> = data.get_mut(m.to).unwrap();
-    to.append(&mut dropped);
-  }
-  let score: String = data
+// The stack drawing comes first, then a blank line and the moves. Blank
+// lines after the last move are ignored.
+fn parse(input: &Input) -> (Stacks, Vec<Movement>) {
+  let (drawing, moves) = records::sections(input).unwrap_or_else(|e| panic!("{e}"));
+  let stacks = Stacks::parse(drawing).unwrap_or_else(|e| panic!("{e}"));
+  let end = moves
+    .lines
+    .iter()
+    .rposition(|m| !m.trim().is_empty())
+    .map_or(0, |i| i + 1);
+  let movements = moves.lines[..end]
     .iter()
-    .map(|list| list.last().unwrap().to_owned())
+    .enumerate()
+    .map(|(i, m)| Movement::from_line(m, moves.line + i))
     .collect();
-  println!("{score}");
+  (stacks, movements)
+}
+
+fn solve(input: &Input, crane: &dyn Crane) -> String {
+  let (mut stacks, movements) = parse(input);
+  let steps = stacks.plan(&movements).unwrap_or_else(|e| panic!("{e}"));
+  for step in steps {
+    crane.apply(&mut stacks, step);
+  }
+  stacks.tops()
+}
+
+pub fn five(options: &Options) {
+  let input = read_data();
+  let crane: &dyn Crane = match options.part() {
+    Part::One => &CrateMover9000,
+    Part::Two => &CrateMover9001,
+  };
+  println!("{}", solve(&input, crane));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{parse, solve, CrateMover9000, CrateMover9001, Stacks};
+  use crate::records::sections;
+  use indoc::indoc;
+
+  fn lines(s: &str) -> Vec<String> {
+    s.lines().map(|l| l.to_owned()).collect()
+  }
+
+  fn drawing(s: &str) -> Result<Stacks, String> {
+    let input = lines(s);
+    let (drawing, _) = sections(&input).unwrap();
+    Stacks::parse(drawing).map_err(|e| e.to_string())
+  }
+
+  #[test]
+  fn stack_drawing() {
+    let (stacks, movements) = parse(&lines(SAMPLE));
+    assert_eq!(stacks.labels, vec!["1", "2", "3"]);
+    assert_eq!(
+      stacks.crates,
+      vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']]
+    );
+    assert_eq!(movements.len(), 4);
+    assert_eq!(movements[0].line, 6);
+
+    let wide = drawing("[A]            [B]\n 8   9  10  11  12\n\nmove 1 from 8 to 12");
+    let wide = wide.unwrap();
+    assert_eq!(wide.labels.len(), 5);
+    assert_eq!(wide.tops(), "A   B");
+
+    // columns count chars, not bytes
+    let accented = drawing("   [B]\nééé 2\n\nm").unwrap();
+    assert_eq!(accented.labels, vec!["ééé", "2"]);
+    assert_eq!(accented.tops(), " B");
+
+    let trailing = format!("{SAMPLE}\n  \n");
+    let (_, movements) = parse(&lines(&trailing));
+    assert_eq!(movements.len(), 4);
+  }
+
+  #[test]
+  fn invalid_drawing() {
+    assert_eq!(
+      drawing("[A]\n    [B]\n 1   2\n\nmove 1 from 1 to 2").unwrap_err(),
+      "line 1: crate `A` floats above stack `1`"
+    );
+    assert_eq!(
+      drawing("    [A]\n 1\n\nmove 1 from 1 to 1").unwrap_err(),
+      "line 1: crate at column 5 is above no label"
+    );
+    assert_eq!(
+      drawing("[A] (B)\n 1   2\n\nm").unwrap_err(),
+      "line 1: expected `[X]` at column 5"
+    );
+    assert_eq!(
+      drawing("[A] [ ]\n 1   2\n\nm").unwrap_err(),
+      "line 1: empty crate at column 5"
+    );
+    assert_eq!(
+      drawing(" 1   1\n\nm").unwrap_err(),
+      "line 1: label `1` is used twice"
+    );
+  }
+
+  #[test]
+  fn invalid_moves() {
+    let (stacks, movements) = parse(&lines(SAMPLE));
+    let mut broken = lines(SAMPLE);
+    broken[7] = "move 4 from 1 to 2".to_string();
+    let (_, too_many) = parse(&broken);
+    assert!(stacks.plan(&movements).is_ok());
+    assert_eq!(
+      stacks.plan(&too_many).unwrap_err().to_string(),
+      "line 8: cannot take 4 crates from stack `1` holding 0"
+    );
+    broken[7] = "move 1 from 1 to 4".to_string();
+    let (_, unknown) = parse(&broken);
+    assert_eq!(
+      stacks.plan(&unknown).unwrap_err().to_string(),
+      "line 8: no stack labelled `4`"
+    );
+  }
+
+  #[test]
+  fn simple() {
+    assert_eq!(solve(&lines(SAMPLE), &CrateMover9000), "CMZ");
+  }
+
+  #[test]
+  fn v2() {
+    assert_eq!(solve(&lines(SAMPLE), &CrateMover9001), "MCD");
+  }
+
+  const SAMPLE: &str = indoc! {"
+        [D]
+    [N] [C]
+    [Z] [M] [P]
+     1   2   3
+
+    move 1 from 2 to 1
+    move 3 from 1 to 3
+    move 2 from 2 to 1
+    move 1 from 1 to 2
+  "};
 }
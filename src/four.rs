@@ -1,33 +1,21 @@
+use crate::cli::{Options, Part};
+use crate::range::{Range, RangeSet};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::{Display, Formatter};
 use std::fs::File;
 use std::io::{prelude::*, BufReader};
 
 type Input = Vec<String>;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Range {
-  min: usize,
-  max: usize,
+fn parse_range(s: &str) -> Range {
+  let (min, max) = s.split_once('-').unwrap();
+  Range::new(min.parse().unwrap(), max.parse().unwrap())
 }
 
-impl Range {
-  fn new(min: usize, max: usize) -> Range {
-    Range { min, max }
-  }
-
-  fn from_str(s: &str) -> Self {
-    let mut parts = s.split('-');
-    let min = parts.next().unwrap().parse().unwrap();
-    let max = parts.next().unwrap().parse().unwrap();
-    Range { min, max }
-  }
-
-  fn contains(&self, other: &Self) -> bool {
-    other.min >= self.min && other.max <= self.max
-  }
-
-  fn overlaps(&self, other: &Self) -> bool {
-    other.min <= self.max && other.max >= self.min
-  }
+fn parse_pair(line: &str) -> (Range, Range) {
+  let (first, second) = line.split_once(',').unwrap();
+  (parse_range(first), parse_range(second))
 }
 
 fn read_data() -> Input {
@@ -39,18 +27,10 @@ fn read_data() -> Input {
 }
 
 fn solve(input: Input) -> usize {
-  let pairs: Vec<(Range, Range)> = input
-    .iter()
-    .map(|line| {
-      let mut parts = line.split(',');
-      let first = Range::from_str(parts.next().unwrap());
-      let second = Range::from_str(parts.next().unwrap());
-      (first, second)
-    })
-    .collect();
+  let pairs: Vec<(Range, Range)> = input.iter().map(|line| parse_pair(line)).collect();
   let mut score: usize = 0;
   for (a, b) in pairs.iter() {
-    if a.contains(b) || b.contains(a) {
+    if a.contains_range(b) || b.contains_range(a) {
       score += 1;
     }
   }
@@ -58,15 +38,7 @@ fn solve(input: Input) -> usize {
 }
 
 fn solve_v2(input: Input) -> usize {
-  let pairs: Vec<(Range, Range)> = input
-    .iter()
-    .map(|line| {
-      let mut parts = line.split(',');
-      let first = Range::from_str(parts.next().unwrap());
-      let second = Range::from_str(parts.next().unwrap());
-      (first, second)
-    })
-    .collect();
+  let pairs: Vec<(Range, Range)> = input.iter().map(|line| parse_pair(line)).collect();
   let mut score: usize = 0;
   for (a, b) in pairs.iter() {
     if a.overlaps(b) {
@@ -76,14 +48,210 @@ fn solve_v2(input: Input) -> usize {
   score
 }
 
-pub fn four() {
+/// Assignment of one elf: `slot` tells the first (1) and second (2) elf of
+/// the pair on `line` apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Elf {
+  line: usize,
+  slot: usize,
+  range: Range,
+}
+
+impl Display for Elf {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "line {} elf {}: {}", self.line, self.slot, self.range)
+  }
+}
+
+// Section ids start at 1 and go up to the last one assigned.
+fn sections(elves: &[Elf]) -> Range {
+  Range::new(1, elves.iter().map(|e| e.range.max).max().unwrap_or(0))
+}
+
+fn elves(input: &Input) -> Vec<Elf> {
+  input
+    .iter()
+    .enumerate()
+    .flat_map(|(i, line)| {
+      let (a, b) = parse_pair(line);
+      [(1, a), (2, b)].map(|(slot, range)| Elf {
+        line: i + 1,
+        slot,
+        range,
+      })
+    })
+    .collect()
+}
+
+/// Number of elves covering each section of `sections`, as maximal runs of
+/// sections sharing the same count. Sections nobody covers, at the edges
+/// too, get runs with a count of 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Coverage {
+  runs: Vec<(Range, usize)>,
+}
+
+impl Coverage {
+  fn new(ranges: impl Iterator<Item = Range>, sections: Range) -> Self {
+    // +1 where a range starts, -1 right after it ends; the bounds of
+    // `sections` change nothing but make sure runs reach them
+    let mut events: Vec<(isize, isize)> = ranges
+      .flat_map(|r| [(r.min, 1), (r.max + 1, -1)])
+      .chain([(sections.min, 0), (sections.max + 1, 0)])
+      .collect();
+    events.sort_unstable();
+    let mut runs: Vec<(Range, usize)> = vec![];
+    let mut count: isize = 0;
+    for (i, &(at, delta)) in events.iter().enumerate() {
+      count += delta;
+      let Some(&(next, _)) = events.get(i + 1) else {
+        break;
+      };
+      if next == at {
+        continue;
+      }
+      let Some(run) = Range::new(at, next - 1).intersection(&sections) else {
+        continue;
+      };
+      match runs.last_mut() {
+        Some((last, c)) if *c == count as usize && last.max + 1 == at => last.max = run.max,
+        _ => runs.push((run, count as usize)),
+      }
+    }
+    Self { runs }
+  }
+
+  fn max(&self) -> usize {
+    self.runs.iter().map(|&(_, c)| c).max().unwrap_or(0)
+  }
+
+  fn sections_with(&self, count: usize) -> RangeSet {
+    self
+      .runs
+      .iter()
+      .filter(|&&(_, c)| c == count)
+      .map(|&(r, _)| r)
+      .collect()
+  }
+
+  // Lowest count over `range`, which must lie within the covered span.
+  fn min_within(&self, range: &Range) -> usize {
+    self
+      .runs
+      .iter()
+      .filter(|(r, _)| r.overlaps(range))
+      .map(|&(_, c)| c)
+      .min()
+      .unwrap_or(0)
+  }
+}
+
+/// Elves whose sections are all covered by someone else too.
+fn redundant(elves: &[Elf], coverage: &Coverage) -> Vec<Elf> {
+  elves
+    .iter()
+    .filter(|e| coverage.min_within(&e.range) >= 2)
+    .copied()
+    .collect()
+}
+
+fn print_sections(label: &str, sections: &RangeSet) {
+  if sections.is_empty() {
+    println!("{label}: none");
+    return;
+  }
+  let runs: Vec<String> = sections.iter().map(|r| r.to_string()).collect();
+  println!("{label} ({} sections): {}", sections.len(), runs.join(", "));
+}
+
+fn print_coverage(input: &Input) {
+  let elves = elves(input);
+  let coverage = Coverage::new(elves.iter().map(|e| e.range), sections(&elves));
+  let max = coverage.max();
+  print_sections(
+    &format!("covered by {max} elves"),
+    &coverage.sections_with(max),
+  );
+  print_sections("uncovered", &coverage.sections_with(0));
+  let redundant = redundant(&elves, &coverage);
+  println!("redundant elves: {}", redundant.len());
+  for elf in redundant.iter() {
+    println!("  {elf}");
+  }
+  // one bar per run of sections with the same number of elves
+  for (range, count) in coverage.runs.iter() {
+    let bar = "#".repeat((count * HISTOGRAM_WIDTH).div_ceil(max.max(1)));
+    println!("{:>9} | {count:>5} {bar}", range.to_string());
+  }
+}
+
+const HISTOGRAM_WIDTH: usize = 50;
+
+/// Crew of every elf, in the order of `elves`, such that no two elves of a
+/// crew overlap, using as few crews as the busiest section requires.
+fn assign_crews(elves: &[Elf]) -> Vec<usize> {
+  let mut order: Vec<usize> = (0..elves.len()).collect();
+  order.sort_by_key(|&i| (elves[i].range.min, elves[i].range.max));
+  // crews by the last section taken so far, earliest free first
+  let mut free: BinaryHeap<Reverse<(isize, usize)>> = BinaryHeap::new();
+  let mut crews = vec![0; elves.len()];
+  for i in order {
+    let range = elves[i].range;
+    let crew = match free.peek() {
+      Some(&Reverse((last, crew))) if last < range.min => {
+        free.pop();
+        crew
+      }
+      _ => free.len(),
+    };
+    crews[i] = crew;
+    free.push(Reverse((range.max, crew)));
+  }
+  crews
+}
+
+fn print_crews(input: &Input) {
+  let elves = elves(input);
+  let crews = assign_crews(&elves);
+  let count = crews.iter().max().map_or(0, |c| c + 1);
+  println!("{count} crews");
+  println!("{:>5} {:>3} {:>9} {:>5}", "line", "elf", "sections", "crew");
+  for (elf, crew) in elves.iter().zip(crews) {
+    println!(
+      "{:>5} {:>3} {:>9} {:>5}",
+      elf.line,
+      elf.slot,
+      elf.range.to_string(),
+      crew + 1
+    );
+  }
+}
+
+pub fn four(options: &Options) {
   let input = read_data();
-  let score = solve_v2(input);
+  if options.flag("coverage") {
+    print_coverage(&input);
+    return;
+  }
+  if options.flag("crews") {
+    print_crews(&input);
+    return;
+  }
+  let score = match options.part() {
+    Part::One => solve(input),
+    Part::Two => solve_v2(input),
+  };
   println!("{score}")
 }
 
 #[cfg(test)]
 mod tests {
+  use super::{assign_crews, elves, redundant, sections, Coverage};
+  use crate::range::{Range, RangeSet};
+
+  fn sample() -> Vec<String> {
+    SAMPLE.lines().map(|l| l.to_string()).collect()
+  }
 
   #[test]
   fn simple() {
@@ -99,6 +267,57 @@ mod tests {
     assert_eq!(score, 4);
   }
 
+  #[test]
+  fn coverage() {
+    let elves = elves(&sample());
+    let coverage = Coverage::new(elves.iter().map(|e| e.range), sections(&elves));
+    assert_eq!(coverage.max(), 8);
+    assert_eq!(coverage.sections_with(8), RangeSet::from(Range::new(6, 6)));
+    assert_eq!(coverage.sections_with(0), RangeSet::from(Range::new(1, 1)));
+    let counts: Vec<usize> = coverage.runs.iter().map(|&(_, c)| c).collect();
+    assert_eq!(counts, vec![0, 4, 5, 7, 8, 6, 4, 1]);
+    let found: Vec<String> = redundant(&elves, &coverage)
+      .iter()
+      .map(|e| e.to_string())
+      .collect();
+    // only 9 is left to the elf assigned 7-9
+    assert_eq!(found.len(), 11);
+    assert!(!found.contains(&"line 3 elf 2: 7-9".to_string()));
+
+    let gappy = Coverage::new(
+      [Range::new(1, 2), Range::new(5, 6), Range::new(2, 2)].into_iter(),
+      Range::new(1, 8),
+    );
+    assert_eq!(gappy.runs.len(), 5);
+    let uncovered: RangeSet = [Range::new(3, 4), Range::new(7, 8)].into_iter().collect();
+    assert_eq!(gappy.sections_with(0), uncovered);
+    assert_eq!(
+      gappy.runs,
+      vec![
+        (Range::new(1, 1), 1),
+        (Range::new(2, 2), 2),
+        (Range::new(3, 4), 0),
+        (Range::new(5, 6), 1),
+        (Range::new(7, 8), 0)
+      ]
+    );
+  }
+
+  #[test]
+  fn crews() {
+    let elves = elves(&sample());
+    let crews = assign_crews(&elves);
+    // as many crews as elves on the busiest section
+    let coverage = Coverage::new(elves.iter().map(|e| e.range), sections(&elves));
+    assert_eq!(crews.iter().max().unwrap() + 1, coverage.max());
+    for (i, a) in elves.iter().enumerate() {
+      for (j, b) in elves.iter().enumerate().skip(i + 1) {
+        assert!(crews[i] != crews[j] || !a.range.overlaps(&b.range));
+      }
+    }
+    assert_eq!(crews, vec![1, 7, 0, 0, 1, 0, 3, 4, 0, 5, 2, 6]);
+  }
+
   const SAMPLE: &str = r#"2-4,6-8
 2-3,4-5
 5-7,7-9
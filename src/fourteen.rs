@@ -1,3 +1,5 @@
+use crate::render::{self, Anchor, Frame, Palette};
+use crate::simulation::Simulation;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{prelude::*, BufReader};
@@ -72,13 +74,20 @@ enum Cell {
   Block,
 }
 
+const ORIGIN: Point = Point::new(500, 0);
+
+// One step drops a single unit of sand until it comes to rest.
 struct Game {
   max: isize,
   map: HashMap<Point, Cell>,
+  // part two: an infinite floor two rows below the lowest rock
+  floor: bool,
+  dropped_sand: usize,
+  finished: bool,
 }
 
 impl Game {
-  fn new(blocks: Vec<Path>) -> Self {
+  fn new(blocks: Vec<Path>, floor: bool) -> Self {
     let mut map = HashMap::new();
     let max: isize = blocks
       .iter()
@@ -101,77 +110,39 @@ impl Game {
         }
       }
     }
-    Game { map, max }
-  }
-
-  fn play(&mut self) -> usize {
-    let mut dropped_sand: usize = 0;
-    const ORIGIN: Point = Point::new(500, 0);
-    'main: loop {
-      let mut current = ORIGIN;
-      //iterations for a sand unit
-      'sand: loop {
-        if (current.y + 1) > self.max {
-          break 'main;
-        }
+    Game {
+      map,
+      max,
+      floor,
+      dropped_sand: 0,
+      finished: false,
+    }
+  }
 
-        let possible_steps = Game::possible_steps(current);
-        for step in possible_steps {
-          let content = self.map.get(&step).unwrap_or(&Cell::Empty);
-          match *content {
-            Cell::Empty => {
-              current = step;
-              continue 'sand;
-            }
-            _ => (),
-          }
-        }
-        //no empty cell found
-        //fill the current cell
-        self.map.insert(current, Cell::Sand);
-        break;
-      }
-      dropped_sand += 1;
+  fn cell(&self, p: Point) -> Cell {
+    match self.map.get(&p) {
+      Some(cell) => *cell,
+      None if self.floor && p.y == self.max + 2 => Cell::Block,
+      None => Cell::Empty,
     }
-    dropped_sand
-  }
-
-  fn play_v2(&mut self) -> usize {
-    let mut dropped_sand: usize = 0;
-    const ORIGIN: Point = Point::new(500, 0);
-    'main: loop {
-      let mut current = ORIGIN;
-      //iterations for a sand unit
-      'sand: loop {
-        let possible_steps = Game::possible_steps(current);
-        for step in possible_steps {
-          let content = self.map.get(&step).unwrap_or_else(|| {
-            if step.y == (self.max + 2) {
-              &Cell::Block
-            } else {
-              &Cell::Empty
-            }
-          });
-          match *content {
-            Cell::Empty => {
-              current = step;
-              continue 'sand;
-            }
-            _ => (),
-          }
-        }
-        //no empty cell found
+  }
 
-        //fill the current cell
-        self.map.insert(current, Cell::Sand);
-        break;
+  // Where a unit of sand dropped from `ORIGIN` comes to rest, or `None` if it
+  // falls into the abyss.
+  fn fall(&self) -> Option<Point> {
+    let mut current = ORIGIN;
+    loop {
+      if !self.floor && (current.y + 1) > self.max {
+        return None;
       }
-      dropped_sand += 1;
-      if current == ORIGIN {
-        break;
+      let next = Game::possible_steps(current)
+        .into_iter()
+        .find(|&step| self.cell(step) == Cell::Empty);
+      match next {
+        Some(step) => current = step,
+        None => return Some(current),
       }
     }
-    dropped_sand
   }
 
   fn possible_steps(prev: Point) -> [Point; 3] {
@@ -180,6 +151,55 @@ impl Game {
   }
 }
 
+impl Simulation for Game {
+  // Sand only piles up, so the amount dropped tells states apart.
+  type Key = usize;
+
+  fn step(&mut self) {
+    if self.finished {
+      return;
+    }
+    match self.fall() {
+      Some(rest) => {
+        self.map.insert(rest, Cell::Sand);
+        self.dropped_sand += 1;
+        self.finished = rest == ORIGIN;
+      }
+      None => self.finished = true,
+    }
+  }
+
+  fn is_finished(&self) -> bool {
+    self.finished
+  }
+
+  fn state_key(&self) -> usize {
+    self.dropped_sand
+  }
+
+  fn render(&self) -> String {
+    let points = || self.map.keys().chain(std::iter::once(&ORIGIN));
+    let min_x = points().map(|p| p.x).min().unwrap();
+    let max_x = points().map(|p| p.x).max().unwrap();
+    let max_y = if self.floor { self.max + 2 } else { self.max };
+    let mut out = String::new();
+    for y in 0..=max_y {
+      for x in min_x..=max_x {
+        let p = Point::new(x, y);
+        let c = match self.cell(p) {
+          Cell::Block => '#',
+          Cell::Sand => 'o',
+          Cell::Empty if p == ORIGIN => '+',
+          Cell::Empty => '.',
+        };
+        out.push(c);
+      }
+      out.push('\n');
+    }
+    out
+  }
+}
+
 fn read_data() -> Input {
   let filename = format!("./resources/14.txt");
   let file: File = File::open(&filename).expect(&format!("Cannot open file {}", &filename));
@@ -193,9 +213,9 @@ fn solve(input: Input) -> usize {
     .into_iter()
     .map(|line| line_to_path(line.as_str()))
     .collect();
-  let mut game = Game::new(paths);
-  let score = game.play();
-  score
+  let mut game = Game::new(paths, false);
+  game.run();
+  game.dropped_sand
 }
 
 fn solve_v2(input: Input) -> usize {
@@ -203,9 +223,9 @@ fn solve_v2(input: Input) -> usize {
     .into_iter()
     .map(|line| line_to_path(line.as_str()))
     .collect();
-  let mut game = Game::new(paths);
-  let score = game.play_v2();
-  score
+  let mut game = Game::new(paths, true);
+  game.run();
+  game.dropped_sand
 }
 
 pub fn fourteen() {
@@ -214,8 +234,40 @@ pub fn fourteen() {
   println!("{score}")
 }
 
+const FRAME_EVERY: usize = 250;
+
+pub fn render() {
+  let input = read_data();
+  let paths = input.iter().map(|line| line_to_path(line)).collect();
+  let mut game = Game::new(paths, true);
+  let palette = Palette::new(&[
+    ('.', render::BLACK),
+    ('#', [120, 120, 120]),
+    ('o', [230, 190, 80]),
+    ('+', [255, 0, 0]),
+  ]);
+  let frames: Vec<Frame> = render::record(&mut game, FRAME_EVERY, usize::MAX, &palette)
+    .iter()
+    .map(|f| f.scaled(2))
+    .collect();
+  render::save_gif(
+    "./output/14/cave.gif",
+    &frames,
+    &palette,
+    5,
+    Anchor::TopLeft,
+  )
+  .unwrap();
+  frames
+    .last()
+    .unwrap()
+    .save_ppm("./output/14/cave.ppm", &palette)
+    .unwrap();
+}
+
 #[cfg(test)]
 mod tests {
+  use crate::simulation::Simulation;
   use indoc::indoc;
 
   #[test]
@@ -225,6 +277,27 @@ mod tests {
     assert_eq!(score, 24);
   }
 
+  #[test]
+  fn render() {
+    let input: super::Input = SAMPLE.lines().map(|l| l.to_string()).collect();
+    let paths = input.iter().map(|l| super::line_to_path(l)).collect();
+    let mut game = super::Game::new(paths, false);
+    game.run_for(5);
+    let expected = indoc! {"
+      ......+...
+      ..........
+      ..........
+      ..........
+      ....#...##
+      ....#...#.
+      ..###...#.
+      ......o.#.
+      ....oooo#.
+      #########.
+    "};
+    assert_eq!(game.render(), expected);
+  }
+
   #[test]
   fn v2() {
     let input: super::Input = SAMPLE.lines().map(|l| l.to_string()).collect();
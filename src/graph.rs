@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+pub type NodeId = usize;
+
+/// Directed graph with weighted edges. Node names are interned to dense ids so
+/// callers can keep per-node data in plain vectors, and every node carries a
+/// payload of type `N`.
+#[derive(Debug, Clone, Default)]
+pub struct Graph<N = ()> {
+  names: Vec<String>,
+  index: HashMap<String, NodeId>,
+  data: Vec<N>,
+  edges: Vec<Vec<(NodeId, usize)>>,
+}
+
+impl<N> Graph<N> {
+  pub fn new() -> Self {
+    Self {
+      names: vec![],
+      index: HashMap::new(),
+      data: vec![],
+      edges: vec![],
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    self.names.len()
+  }
+
+  pub fn id(&self, name: &str) -> Option<NodeId> {
+    self.index.get(name).copied()
+  }
+
+  pub fn name(&self, id: NodeId) -> &str {
+    &self.names[id]
+  }
+
+  pub fn data(&self, id: NodeId) -> &N {
+    &self.data[id]
+  }
+
+  pub fn ids(&self) -> std::ops::Range<NodeId> {
+    0..self.len()
+  }
+
+  /// Adds a node, or replaces the payload of the node already using `name`.
+  pub fn add_node(&mut self, name: &str, data: N) -> NodeId {
+    if let Some(id) = self.id(name) {
+      self.data[id] = data;
+      return id;
+    }
+    let id = self.names.len();
+    self.names.push(name.to_string());
+    self.index.insert(name.to_string(), id);
+    self.data.push(data);
+    self.edges.push(vec![]);
+    id
+  }
+
+  pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: usize) {
+    self.edges[from].push((to, weight));
+  }
+
+  pub fn neighbours(&self, id: NodeId) -> &[(NodeId, usize)] {
+    &self.edges[id]
+  }
+
+  /// Floyd–Warshall over the whole graph.
+  pub fn all_pairs_distances(&self) -> Distances {
+    let n = self.len();
+    let mut distances = Distances {
+      size: n,
+      values: vec![None; n * n],
+    };
+    for a in self.ids() {
+      distances.values[a * n + a] = Some(0);
+      for &(b, weight) in self.neighbours(a) {
+        let current = &mut distances.values[a * n + b];
+        *current = Some(current.map_or(weight, |w| w.min(weight)));
+      }
+    }
+    for k in 0..n {
+      for i in 0..n {
+        let Some(ik) = distances.values[i * n + k] else {
+          continue;
+        };
+        for j in 0..n {
+          if let Some(kj) = distances.values[k * n + j] {
+            let current = &mut distances.values[i * n + j];
+            if current.is_none_or(|d| ik + kj < d) {
+              *current = Some(ik + kj);
+            }
+          }
+        }
+      }
+    }
+    distances
+  }
+
+  /// Graph made of `keep` only, in that order, with an edge between every
+  /// pair of kept nodes weighted by their shortest distance in this graph.
+  pub fn compress(&self, keep: &[NodeId]) -> Graph<N>
+  where
+    N: Clone,
+  {
+    let distances = self.all_pairs_distances();
+    let mut compressed = Graph::new();
+    for &id in keep {
+      compressed.add_node(self.name(id), self.data(id).clone());
+    }
+    for (i, &a) in keep.iter().enumerate() {
+      for (j, &b) in keep.iter().enumerate() {
+        if i == j {
+          continue;
+        }
+        if let Some(d) = distances.get(a, b) {
+          compressed.add_edge(i, j, d);
+        }
+      }
+    }
+    compressed
+  }
+
+  /// Graphviz rendering, labelling each node with `label`.
+  pub fn to_dot_with(&self, label: impl Fn(NodeId, &N) -> String) -> String {
+    let mut dot = String::from("digraph {\n");
+    for id in self.ids() {
+      writeln!(dot, "  {} [label=\"{}\"];", id, label(id, self.data(id))).unwrap();
+    }
+    for a in self.ids() {
+      for &(b, weight) in self.neighbours(a) {
+        writeln!(dot, "  {a} -> {b} [label=\"{weight}\"];").unwrap();
+      }
+    }
+    dot.push_str("}\n");
+    dot
+  }
+
+  pub fn to_dot(&self) -> String {
+    self.to_dot_with(|id, _| self.name(id).to_string())
+  }
+}
+
+impl<N: Default> Graph<N> {
+  /// Id of `name`, creating the node with a default payload if needed, so
+  /// edges can point to nodes that are described later.
+  pub fn intern(&mut self, name: &str) -> NodeId {
+    match self.id(name) {
+      Some(id) => id,
+      None => self.add_node(name, N::default()),
+    }
+  }
+}
+
+/// Shortest distances between every pair of nodes; `None` if unreachable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Distances {
+  size: usize,
+  values: Vec<Option<usize>>,
+}
+
+impl Distances {
+  pub fn get(&self, from: NodeId, to: NodeId) -> Option<usize> {
+    self.values[from * self.size + to]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Graph;
+
+  // a - b - c - d, plus a heavy shortcut a -> d
+  fn sample() -> Graph<usize> {
+    let mut g: Graph<usize> = Graph::new();
+    let a = g.add_node("a", 1);
+    let b = g.intern("b");
+    let c = g.intern("c");
+    let d = g.add_node("d", 4);
+    for (x, y) in [(a, b), (b, c), (c, d)] {
+      g.add_edge(x, y, 1);
+      g.add_edge(y, x, 1);
+    }
+    g.add_edge(a, d, 10);
+    g
+  }
+
+  #[test]
+  fn interning() {
+    let mut g = sample();
+    assert_eq!(g.len(), 4);
+    assert_eq!(g.id("c"), Some(2));
+    assert_eq!(g.intern("c"), 2);
+    assert_eq!(g.name(3), "d");
+    assert_eq!(*g.data(1), 0);
+    assert_eq!(g.add_node("b", 7), 1);
+    assert_eq!(*g.data(1), 7);
+    assert_eq!(g.id("z"), None);
+  }
+
+  #[test]
+  fn floyd_warshall() {
+    let g = sample();
+    let d = g.all_pairs_distances();
+    assert_eq!(d.get(0, 3), Some(3));
+    assert_eq!(d.get(3, 0), Some(3));
+    assert_eq!(d.get(1, 1), Some(0));
+
+    let mut h: Graph = Graph::new();
+    let x = h.intern("x");
+    let y = h.intern("y");
+    h.add_edge(x, y, 2);
+    let d = h.all_pairs_distances();
+    assert_eq!(d.get(x, y), Some(2));
+    assert_eq!(d.get(y, x), None);
+  }
+
+  #[test]
+  fn compress() {
+    let g = sample();
+    let compressed = g.compress(&[3, 0]);
+    assert_eq!(compressed.len(), 2);
+    assert_eq!(compressed.name(0), "d");
+    assert_eq!(*compressed.data(0), 4);
+    assert_eq!(compressed.neighbours(0), &[(1, 3)]);
+    assert_eq!(compressed.neighbours(1), &[(0, 3)]);
+  }
+
+  #[test]
+  fn dot() {
+    let mut g: Graph = Graph::new();
+    let a = g.intern("a");
+    let b = g.intern("b");
+    g.add_edge(a, b, 5);
+    let expected =
+      "digraph {\n  0 [label=\"a\"];\n  1 [label=\"b\"];\n  0 -> 1 [label=\"5\"];\n}\n";
+    assert_eq!(g.to_dot(), expected);
+  }
+}
@@ -1,48 +1,76 @@
+mod bitset;
+mod cli;
+mod cycle;
 mod eight;
 mod eleven;
+mod expr;
+mod extract;
 mod fifteen;
 mod five;
 mod four;
 mod fourteen;
+mod graph;
 mod nine;
+mod numeric;
+mod ocr;
 mod one;
+mod parser;
 mod point;
+mod range;
+mod records;
+mod render;
+mod search;
 mod seven;
 mod seventeen;
+mod simulation;
 mod six;
 mod sixteen;
 mod ten;
 mod thirteen;
 mod three;
+mod tree;
 mod twelve;
 mod two;
 
 use std::env;
 fn main() {
   let args: Vec<String> = env::args().collect();
-  if args.len() != 2 {
-    println!("Invalid number of arguments")
+  if args.len() < 2 {
+    println!("Invalid number of arguments");
+    return;
   }
 
   let day: &str = args.get(1).unwrap();
+  let options = cli::Options::new(&args[2..]);
+
+  if options.command() == Some("render") {
+    match day {
+      "9" => crate::nine::render(),
+      "14" => crate::fourteen::render(),
+      "16" => crate::sixteen::render(),
+      "17" => crate::seventeen::render(),
+      _ => println!("Nothing to render for day {day}"),
+    }
+    return;
+  }
 
   match day {
-    "1" => crate::one::one(),
-    "2" => crate::two::two(),
-    "3" => crate::three::three(),
-    "4" => crate::four::four(),
-    "5" => crate::five::five(),
+    "1" => crate::one::one(&options),
+    "2" => crate::two::two(&options),
+    "3" => crate::three::three(&options),
+    "4" => crate::four::four(&options),
+    "5" => crate::five::five(&options),
     "6" => crate::six::six(),
     "7" => crate::seven::seven(),
     "8" => crate::eight::eight(),
     "9" => crate::nine::nine(),
     "10" => crate::ten::ten(),
-    "11" => crate::eleven::eleven(),
+    "11" => crate::eleven::eleven(&options),
     "12" => crate::twelve::twelve(),
     "13" => crate::thirteen::thirteen(),
     "14" => crate::fourteen::fourteen(),
     "15" => crate::fifteen::fifteen(),
-    "15" => crate::sixteen::sixteen(),
+    "16" => crate::sixteen::sixteen(),
     "17" => crate::seventeen::seventeen(),
     _ => unreachable!(),
   }
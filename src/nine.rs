@@ -1,3 +1,5 @@
+use crate::render::{self, Anchor, Frame, Palette};
+use crate::simulation::Simulation;
 use std::collections::HashSet;
 use std::fs::File;
 use std::io::{prelude::*, BufReader};
@@ -101,35 +103,95 @@ const ZERO: Point = Point::new(0, 0);
 const SNAKE_LEN: usize = 10;
 const LAST: usize = SNAKE_LEN - 1;
 
-fn solve(input: Input) -> usize {
-  let snake = &mut [ZERO; SNAKE_LEN];
-  let mut visited: HashSet<Point> = Default::default();
-  visited.insert(ZERO);
-  let iter = input.iter().map(Instruction::from_line);
-  for instruction in iter {
-    for _ in 0..instruction.count {
-      {
-        let head = &mut snake[0];
-        head.move_to(instruction.dir);
+// One step moves the head a single cell and lets the rest of the rope follow.
+struct Rope {
+  snake: [Point; SNAKE_LEN],
+  moves: Vec<Direction>,
+  next: usize,
+  visited: HashSet<Point>,
+}
+
+impl Rope {
+  fn new(input: &Input) -> Self {
+    let moves = input
+      .iter()
+      .map(Instruction::from_line)
+      .flat_map(|i| std::iter::repeat_n(i.dir, i.count))
+      .collect();
+    Self {
+      snake: [ZERO; SNAKE_LEN],
+      moves,
+      next: 0,
+      visited: HashSet::from([ZERO]),
+    }
+  }
+}
+
+impl Simulation for Rope {
+  type Key = (usize, [Point; SNAKE_LEN]);
+
+  fn step(&mut self) {
+    let Some(&dir) = self.moves.get(self.next) else {
+      return;
+    };
+    self.next += 1;
+    let snake = &mut self.snake;
+    snake[0].move_to(dir);
+    for i in 1..SNAKE_LEN {
+      let head = snake[i - 1];
+      let tail = &mut snake[i];
+      if head.is_adjacent(tail) {
+        continue;
       }
-      for i in 1..SNAKE_LEN {
-        let head = snake[i - 1];
-        let tail = &mut snake[i];
-        if head.is_adjacent(tail) {
-          continue;
-        }
-        let mut diff = head - *tail;
-        diff.x = diff.x.clamp(-1, 1);
-        diff.y = diff.y.clamp(-1, 1);
-        *tail = *tail + diff;
-        if i == LAST {
-          visited.insert(*tail);
-        }
+      let mut diff = head - *tail;
+      diff.x = diff.x.clamp(-1, 1);
+      diff.y = diff.y.clamp(-1, 1);
+      *tail = *tail + diff;
+      if i == LAST {
+        self.visited.insert(*tail);
       }
     }
   }
 
-  visited.iter().count()
+  fn is_finished(&self) -> bool {
+    self.next == self.moves.len()
+  }
+
+  fn state_key(&self) -> Self::Key {
+    (self.next, self.snake)
+  }
+
+  // Knots are numbered from the head (`H`), `#` marks cells the tail visited
+  // and `s` the start. `X` grows upwards and `Y` to the right.
+  fn render(&self) -> String {
+    let points = || self.snake.iter().chain(self.visited.iter());
+    let min_x = points().map(|p| p.x).min().unwrap();
+    let max_x = points().map(|p| p.x).max().unwrap();
+    let min_y = points().map(|p| p.y).min().unwrap();
+    let max_y = points().map(|p| p.y).max().unwrap();
+    let mut out = String::new();
+    for x in (min_x..=max_x).rev() {
+      for y in min_y..=max_y {
+        let p = Point::new(x, y);
+        let c = match self.snake.iter().position(|&k| k == p) {
+          Some(0) => 'H',
+          Some(i) => char::from_digit(i as u32, 10).unwrap(),
+          None if p == ZERO => 's',
+          None if self.visited.contains(&p) => '#',
+          None => '.',
+        };
+        out.push(c);
+      }
+      out.push('\n');
+    }
+    out
+  }
+}
+
+fn solve(input: Input) -> usize {
+  let mut rope = Rope::new(&input);
+  rope.run();
+  rope.visited.len()
 }
 
 pub fn nine() {
@@ -138,6 +200,37 @@ pub fn nine() {
   println!("{score}")
 }
 
+const FRAME_EVERY: usize = 100;
+
+pub fn render() {
+  let input = read_data();
+  let mut rope = Rope::new(&input);
+  let knot = [255, 200, 0];
+  let palette = Palette::new(&[
+    ('.', render::BLACK),
+    ('#', [40, 90, 160]),
+    ('s', [255, 0, 0]),
+    ('H', render::WHITE),
+    ('1', knot),
+    ('2', knot),
+    ('3', knot),
+    ('4', knot),
+    ('5', knot),
+    ('6', knot),
+    ('7', knot),
+    ('8', knot),
+    ('9', knot),
+  ]);
+  let frames: Vec<Frame> = render::record(&mut rope, FRAME_EVERY, usize::MAX, &palette);
+  render::save_gif("./output/9/rope.gif", &frames, &palette, 4, Anchor::TopLeft).unwrap();
+  frames
+    .last()
+    .unwrap()
+    .scaled(2)
+    .save_ppm("./output/9/rope.ppm", &palette)
+    .unwrap();
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -0,0 +1,148 @@
+/// Greatest common divisor, always non-negative. `gcd(0, 0)` is 0.
+pub fn gcd(a: isize, b: isize) -> isize {
+  gcd_i128(a as i128, b as i128) as isize
+}
+
+/// `gcd` for the wider intermediate values of exact arithmetic.
+pub fn gcd_i128(a: i128, b: i128) -> i128 {
+  let (mut a, mut b) = (a.abs(), b.abs());
+  while b != 0 {
+    (a, b) = (b, a % b);
+  }
+  a
+}
+
+/// Least common multiple, always non-negative. Panics if it does not fit.
+pub fn lcm(a: isize, b: isize) -> isize {
+  if a == 0 || b == 0 {
+    return 0;
+  }
+  (a / gcd(a, b))
+    .checked_mul(b)
+    .map(isize::abs)
+    .expect("lcm overflow")
+}
+
+/// Smallest number every value divides; 1 for no values.
+pub fn lcm_all(values: impl IntoIterator<Item = isize>) -> isize {
+  values.into_iter().fold(1, lcm)
+}
+
+/// `(g, x, y)` with `a * x + b * y == g == gcd(a, b)`.
+pub fn extended_gcd(a: isize, b: isize) -> (isize, isize, isize) {
+  let (mut old_r, mut r) = (a, b);
+  let (mut old_x, mut x) = (1, 0);
+  let (mut old_y, mut y) = (0, 1);
+  while r != 0 {
+    let q = old_r / r;
+    (old_r, r) = (r, old_r - q * r);
+    (old_x, x) = (x, old_x - q * x);
+    (old_y, y) = (y, old_y - q * y);
+  }
+  if old_r < 0 {
+    (-old_r, -old_x, -old_y)
+  } else {
+    (old_r, old_x, old_y)
+  }
+}
+
+/// `a mod m` in `0..m`, whatever the sign of `a`.
+pub fn modulo(a: isize, m: isize) -> isize {
+  a.rem_euclid(m)
+}
+
+pub fn add_mod(a: isize, b: isize, m: isize) -> isize {
+  ((a as i128 + b as i128).rem_euclid(m as i128)) as isize
+}
+
+pub fn sub_mod(a: isize, b: isize, m: isize) -> isize {
+  ((a as i128 - b as i128).rem_euclid(m as i128)) as isize
+}
+
+/// `a * b mod m` without overflowing, however large the operands are.
+pub fn mul_mod(a: isize, b: isize, m: isize) -> isize {
+  ((a as i128 * b as i128).rem_euclid(m as i128)) as isize
+}
+
+pub fn pow_mod(base: isize, mut exp: u32, m: isize) -> isize {
+  let mut base = modulo(base, m);
+  let mut result = modulo(1, m);
+  while exp > 0 {
+    if exp & 1 == 1 {
+      result = mul_mod(result, base, m);
+    }
+    base = mul_mod(base, base, m);
+    exp >>= 1;
+  }
+  result
+}
+
+/// `x` in `0..m` with `a * x == 1 (mod m)`, if `a` and `m` are coprime.
+pub fn mod_inverse(a: isize, m: isize) -> Option<isize> {
+  let (g, x, _) = extended_gcd(modulo(a, m), m);
+  (g == 1).then(|| modulo(x, m))
+}
+
+/// Chinese remainder theorem: the `(x, n)` such that `x == r (mod m)` for
+/// every `(r, m)` given, `x` in `0..n` and `n` the lcm of the moduli. Moduli
+/// do not need to be coprime; `None` when the congruences contradict.
+pub fn crt(congruences: &[(isize, isize)]) -> Option<(isize, isize)> {
+  let mut x = 0;
+  let mut n = 1;
+  for &(r, m) in congruences {
+    // x + n * k == r (mod m)  =>  n * k == r - x (mod m)
+    let (g, inverse, _) = extended_gcd(n, m);
+    let diff = r - x;
+    if diff % g != 0 {
+      return None;
+    }
+    let step = m / g;
+    let k = mul_mod(diff / g, inverse, step);
+    let next = lcm(n, m);
+    x = add_mod(x, mul_mod(n, k, next), next);
+    n = next;
+  }
+  Some((x, n))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{
+    crt, extended_gcd, gcd, gcd_i128, lcm, lcm_all, mod_inverse, mul_mod, pow_mod, sub_mod,
+  };
+
+  #[test]
+  fn divisors() {
+    assert_eq!(gcd(12, 18), 6);
+    assert_eq!(gcd(-12, 18), 6);
+    assert_eq!(gcd(0, 5), 5);
+    assert_eq!(gcd_i128(i128::from(i64::MAX) * 6, -9), 3);
+    assert_eq!(lcm(4, 6), 12);
+    assert_eq!(lcm(-4, 6), 12);
+    assert_eq!(lcm_all([23, 19, 13, 17]), 96577);
+    assert_eq!(lcm_all([2, 4, 6]), 12);
+    let (g, x, y) = extended_gcd(240, 46);
+    assert_eq!(g, 2);
+    assert_eq!(240 * x + 46 * y, 2);
+  }
+
+  #[test]
+  fn modular() {
+    let big = isize::MAX - 1;
+    assert_eq!(mul_mod(big, big, isize::MAX), 1);
+    assert_eq!(sub_mod(2, 5, 7), 4);
+    assert_eq!(pow_mod(3, 200, 13), 9);
+    assert_eq!(pow_mod(5, 0, 1), 0);
+    assert_eq!(mod_inverse(3, 11), Some(4));
+    assert_eq!(mod_inverse(-3, 11), Some(7));
+    assert_eq!(mod_inverse(4, 8), None);
+  }
+
+  #[test]
+  fn chinese_remainder() {
+    assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+    assert_eq!(crt(&[(1, 4), (3, 6)]), Some((9, 12)));
+    assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+    assert_eq!(crt(&[]), Some((0, 1)));
+  }
+}
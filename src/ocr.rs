@@ -0,0 +1,236 @@
+use std::fmt::{Display, Formatter};
+
+// Block letters as the puzzles draw them, lit pixels being `#`. Glyphs sit on
+// a fixed pitch: the glyph width plus `spacing` blank columns.
+struct Font {
+  width: usize,
+  height: usize,
+  spacing: usize,
+  glyphs: &'static [(char, &'static str)],
+}
+
+const SMALL: Font = Font {
+  width: 4,
+  height: 6,
+  spacing: 1,
+  glyphs: &[
+    ('A', ".##.#..##..######..##..#"),
+    ('B', "###.#..####.#..##..####."),
+    ('C', ".##.#..##...#...#..#.##."),
+    ('E', "#####...###.#...#...####"),
+    ('F', "#####...###.#...#...#..."),
+    ('G', ".##.#..##...#.###..#.###"),
+    ('H', "#..##..######..##..##..#"),
+    ('I', ".###..#...#...#...#..###"),
+    ('J', "..##...#...#...##..#.##."),
+    ('K', "#..##.#.##..#.#.#.#.#..#"),
+    ('L', "#...#...#...#...#...####"),
+    ('O', ".##.#..##..##..##..#.##."),
+    ('P', "###.#..##..####.#...#..."),
+    ('R', "###.#..##..####.#.#.#..#"),
+    ('S', ".####...#....##....####."),
+    ('U', "#..##..##..##..##..#.##."),
+    ('Y', "#...#....#.#..#...#...#."),
+    ('Z', "####...#..#..#..#...####"),
+  ],
+};
+
+const LARGE: Font = Font {
+  width: 6,
+  height: 10,
+  spacing: 2,
+  glyphs: &[
+    (
+      'A',
+      "..##...#..#.#....##....##....########....##....##....##....#",
+    ),
+    (
+      'B',
+      "#####.#....##....##....######.#....##....##....##....######.",
+    ),
+    (
+      'C',
+      ".####.#....##.....#.....#.....#.....#.....#.....#....#.####.",
+    ),
+    (
+      'E',
+      "#######.....#.....#.....#####.#.....#.....#.....#.....######",
+    ),
+    (
+      'F',
+      "#######.....#.....#.....#####.#.....#.....#.....#.....#.....",
+    ),
+    (
+      'G',
+      ".####.#....##.....#.....#.....#..####....##....##...##.###.#",
+    ),
+    (
+      'H',
+      "#....##....##....##....########....##....##....##....##....#",
+    ),
+    (
+      'J',
+      "...###....#.....#.....#.....#.....#.....#.#...#.#...#..###..",
+    ),
+    (
+      'K',
+      "#....##...#.#..#..#.#...##....##....#.#...#..#..#...#.#....#",
+    ),
+    (
+      'L',
+      "#.....#.....#.....#.....#.....#.....#.....#.....#.....######",
+    ),
+    (
+      'N',
+      "#....###...###...##.#..##.#..##..#.##..#.##...###...###....#",
+    ),
+    (
+      'P',
+      "#####.#....##....##....######.#.....#.....#.....#.....#.....",
+    ),
+    (
+      'R',
+      "#####.#....##....##....######.#..#..#...#.#...#.#....##....#",
+    ),
+    (
+      'X',
+      "#....##....#.#..#..#..#...##....##...#..#..#..#.#....##....#",
+    ),
+    (
+      'Z',
+      "######.....#.....#....#....#....#....#....#.....#.....######",
+    ),
+  ],
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+  /// Only 6 and 10 row tall lettering is known.
+  UnsupportedHeight(usize),
+  /// Glyph at position `index` matches no letter; `glyph` shows it.
+  UnknownGlyph { index: usize, glyph: String },
+}
+
+impl Display for OcrError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      OcrError::UnsupportedHeight(h) => write!(f, "no font is {h} rows tall"),
+      OcrError::UnknownGlyph { index, glyph } => {
+        write!(f, "glyph {index} is not a known letter:\n{glyph}")
+      }
+    }
+  }
+}
+
+impl std::error::Error for OcrError {}
+
+fn is_lit(c: char) -> bool {
+  c == '#' || c == '█'
+}
+
+/// Reads the letters drawn on `screen`, one text row per pixel row. `#` (or
+/// `█`) is lit, anything else is dark. The font is picked from the height.
+pub fn recognize(screen: &str) -> Result<String, OcrError> {
+  let rows: Vec<Vec<bool>> = screen
+    .lines()
+    .map(|l| l.chars().map(is_lit).collect())
+    .filter(|row: &Vec<bool>| !row.is_empty())
+    .collect();
+  let font = match rows.len() {
+    6 => SMALL,
+    10 => LARGE,
+    h => return Err(OcrError::UnsupportedHeight(h)),
+  };
+  let pixel = |x: usize, y: usize| rows[y].get(x).copied().unwrap_or(false);
+  let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+  let pitch = font.width + font.spacing;
+  let count = (columns + font.spacing) / pitch;
+
+  (0..count)
+    .map(|index| {
+      let glyph: String = (0..font.height)
+        .flat_map(|y| (0..font.width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+          if pixel(index * pitch + x, y) {
+            '#'
+          } else {
+            '.'
+          }
+        })
+        .collect();
+      font
+        .glyphs
+        .iter()
+        .find(|(_, g)| *g == glyph)
+        .map(|(c, _)| *c)
+        .ok_or_else(|| {
+          let rows: Vec<&str> = (0..font.height)
+            .map(|y| &glyph[y * font.width..(y + 1) * font.width])
+            .collect();
+          OcrError::UnknownGlyph {
+            index,
+            glyph: rows.join("\n"),
+          }
+        })
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{recognize, OcrError, LARGE, SMALL};
+  use indoc::indoc;
+
+  #[test]
+  fn fonts_are_well_formed() {
+    for font in [SMALL, LARGE] {
+      for (c, glyph) in font.glyphs {
+        assert_eq!(glyph.len(), font.width * font.height, "{c}");
+      }
+    }
+  }
+
+  #[test]
+  fn small() {
+    let screen = indoc! {"
+      ###...##..#....###..###..####..##..#..#.
+      #..#.#..#.#....#..#.#..#....#.#..#.#..#.
+      #..#.#....#....#..#.###....#..#..#.#..#.
+      ###..#.##.#....###..#..#..#...####.#..#.
+      #.#..#..#.#....#.#..#..#.#....#..#.#..#.
+      #..#..###.####.#..#.###..####.#..#..##..
+    "};
+    assert_eq!(recognize(screen), Ok("RGLRBZAU".to_string()));
+  }
+
+  #[test]
+  fn large() {
+    let screen = indoc! {"
+      #....#..######
+      #....#..#.....
+      .#..#...#.....
+      .#..#...#.....
+      ..##....#####.
+      ..##....#.....
+      .#..#...#.....
+      .#..#...#.....
+      #....#..#.....
+      #....#..######
+    "};
+    assert_eq!(recognize(screen), Ok("XE".to_string()));
+  }
+
+  #[test]
+  fn errors() {
+    assert_eq!(recognize("#\n#\n"), Err(OcrError::UnsupportedHeight(2)));
+    let screen = "#.#.\n".repeat(6);
+    let err = recognize(&screen).unwrap_err();
+    assert_eq!(
+      err,
+      OcrError::UnknownGlyph {
+        index: 0,
+        glyph: ["#.#."; 6].join("\n"),
+      }
+    );
+  }
+}
@@ -1,34 +1,133 @@
+use crate::cli::{Options, Part};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::fs::File;
-use std::io::{prelude::*, BufReader};
+use std::io::{BufRead, BufReader};
 
-fn read_data() -> Vec<Vec<usize>> {
+fn open_data() -> BufReader<File> {
   let filename = format!("./resources/1.txt");
   let file: File = File::open(&filename).expect(&format!("Cannot open file {}", &filename));
-  let reader = BufReader::new(file);
-  let mut line_iter = reader.lines();
-  let mut lists: Vec<Vec<usize>> = vec![];
-  let mut current: Vec<usize> = vec![];
-
-  while let Some(Ok(line)) = line_iter.next() {
-    if line.len() == 0 {
-      lists.push(current);
-      current = vec![];
-      continue;
+  BufReader::new(file)
+}
+
+// Calories carried by each elf, read one line at a time into a reused buffer
+// so that inputs of any size run in constant memory.
+struct ElfTotals<R: BufRead> {
+  reader: R,
+  line: String,
+  line_number: usize,
+}
+
+fn elf_totals<R: BufRead>(reader: R) -> ElfTotals<R> {
+  ElfTotals {
+    reader,
+    line: String::new(),
+    line_number: 0,
+  }
+}
+
+impl<R: BufRead> Iterator for ElfTotals<R> {
+  type Item = usize;
+
+  fn next(&mut self) -> Option<usize> {
+    let mut total: Option<usize> = None;
+    loop {
+      self.line.clear();
+      let read = self.reader.read_line(&mut self.line).unwrap();
+      if read == 0 {
+        return total;
+      }
+      self.line_number += 1;
+      let line = self.line.trim();
+      if line.is_empty() {
+        if total.is_some() {
+          return total;
+        }
+        continue;
+      }
+      let calories: usize = line
+        .parse()
+        .unwrap_or_else(|_| panic!("line {}: invalid calories `{line}`", self.line_number));
+      total = Some(total.unwrap_or(0) + calories);
     }
-    let value: usize = line.parse().unwrap();
-    current.push(value);
   }
-  if current.len() != 0 {
-    lists.push(current);
+}
+
+// The `n` largest values, largest first, keeping at most `n` of them around.
+fn top_n(values: impl Iterator<Item = usize>, n: usize) -> Vec<usize> {
+  // not sized from `n`, which comes straight from the command line
+  let mut heap: BinaryHeap<Reverse<usize>> = BinaryHeap::new();
+  for v in values {
+    heap.push(Reverse(v));
+    if heap.len() > n {
+      heap.pop();
+    }
   }
-  lists
+  heap
+    .into_sorted_vec()
+    .into_iter()
+    .map(|Reverse(v)| v)
+    .collect()
 }
 
-pub fn one() {
-  let lists = read_data();
-  let mut summaries: Vec<usize> = lists.iter().map(|list| list.iter().sum()).collect();
-  summaries.sort();
-  summaries.reverse();
-  let max: usize = summaries.iter().take(3).sum();
-  println!("{max}");
+const TOP: usize = 3;
+
+fn solve(reader: impl BufRead) -> usize {
+  top_n(elf_totals(reader), 1).first().copied().unwrap_or(0)
+}
+
+fn solve_v2(reader: impl BufRead, top: usize) -> usize {
+  top_n(elf_totals(reader), top).iter().sum()
+}
+
+pub fn one(options: &Options) {
+  let score = match options.part() {
+    Part::One => solve(open_data()),
+    Part::Two => solve_v2(open_data(), options.parsed("top").unwrap_or(TOP)),
+  };
+  println!("{score}");
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{elf_totals, solve, solve_v2, top_n, TOP};
+  use indoc::indoc;
+
+  #[test]
+  fn simple() {
+    assert_eq!(solve(SAMPLE.as_bytes()), 24000);
+  }
+
+  #[test]
+  fn v2() {
+    assert_eq!(solve_v2(SAMPLE.as_bytes(), TOP), 45000);
+    assert_eq!(solve_v2(SAMPLE.as_bytes(), 10), 55000);
+  }
+
+  #[test]
+  fn streaming() {
+    let totals: Vec<usize> = elf_totals("\n\n1\n2\n\n\n3\n".as_bytes()).collect();
+    assert_eq!(totals, vec![3, 3]);
+    assert_eq!(top_n([5, 1, 9, 3, 7].into_iter(), 2), vec![9, 7]);
+    assert_eq!(top_n([5, 1].into_iter(), 0), Vec::<usize>::new());
+    assert_eq!(top_n([5, 1].into_iter(), usize::MAX), vec![5, 1]);
+    assert_eq!(solve_v2(SAMPLE.as_bytes(), 100_000_000_000), 55000);
+  }
+
+  const SAMPLE: &str = indoc! {"
+    1000
+    2000
+    3000
+
+    4000
+
+    5000
+    6000
+
+    7000
+    8000
+    9000
+
+    10000
+  "};
 }
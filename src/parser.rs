@@ -0,0 +1,256 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+  pub line: Option<usize>,
+  pub column: usize,
+  pub message: String,
+}
+
+impl ParseError {
+  /// Attaches the (1-based) line number when the error bubbles up from a
+  /// multi-line record.
+  pub fn at_line(self, line: usize) -> Self {
+    Self {
+      line: Some(line),
+      ..self
+    }
+  }
+}
+
+impl Display for ParseError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self.line {
+      Some(line) => write!(f, "line {}, column {}: {}", line, self.column, self.message),
+      None => write!(f, "column {}: {}", self.column, self.message),
+    }
+  }
+}
+
+impl std::error::Error for ParseError {}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// Cursor over a single line. Every method skips the whitespace in front of
+/// what it reads, so callers only describe the tokens they expect.
+#[derive(Debug, Clone)]
+pub struct Parser<'a> {
+  input: &'a str,
+  pos: usize,
+}
+
+fn is_word_char(c: char) -> bool {
+  c.is_ascii_alphanumeric() || c == '_'
+}
+
+impl<'a> Parser<'a> {
+  pub fn new(input: &'a str) -> Self {
+    Self { input, pos: 0 }
+  }
+
+  pub fn rest(&self) -> &'a str {
+    &self.input[self.pos..]
+  }
+
+  pub fn error(&self, message: impl Into<String>) -> ParseError {
+    ParseError {
+      line: None,
+      column: self.pos + 1,
+      message: message.into(),
+    }
+  }
+
+  pub fn skip_whitespace(&mut self) {
+    let rest = self.rest();
+    self.pos += rest.len() - rest.trim_start().len();
+  }
+
+  pub fn peek(&mut self) -> Option<char> {
+    self.skip_whitespace();
+    self.rest().chars().next()
+  }
+
+  pub fn is_done(&mut self) -> bool {
+    self.peek().is_none()
+  }
+
+  pub fn end(&mut self) -> ParseResult<()> {
+    if self.is_done() {
+      Ok(())
+    } else {
+      Err(self.error(format!("unexpected `{}`", self.rest())))
+    }
+  }
+
+  /// Consumes `literal` if the input continues with it.
+  pub fn try_literal(&mut self, literal: &str) -> bool {
+    self.skip_whitespace();
+    if self.rest().starts_with(literal) {
+      self.pos += literal.len();
+      true
+    } else {
+      false
+    }
+  }
+
+  pub fn literal(&mut self, literal: &str) -> ParseResult<()> {
+    if self.try_literal(literal) {
+      Ok(())
+    } else {
+      Err(self.error(format!("expected `{literal}`")))
+    }
+  }
+
+  /// Like `try_literal`, but `keyword` must not be the prefix of a longer word.
+  pub fn try_keyword(&mut self, keyword: &str) -> bool {
+    let start = self.pos;
+    if self.try_literal(keyword) && !self.rest().starts_with(is_word_char) {
+      return true;
+    }
+    self.pos = start;
+    false
+  }
+
+  pub fn keyword(&mut self, keyword: &str) -> ParseResult<()> {
+    if self.try_keyword(keyword) {
+      Ok(())
+    } else {
+      Err(self.error(format!("expected `{keyword}`")))
+    }
+  }
+
+  /// Matches the first of `options` found at the cursor.
+  pub fn one_of<T: Copy>(&mut self, options: &[(&str, T)]) -> ParseResult<T> {
+    for (literal, value) in options {
+      if self.try_literal(literal) {
+        return Ok(*value);
+      }
+    }
+    let expected: Vec<String> = options.iter().map(|(l, _)| format!("`{l}`")).collect();
+    Err(self.error(format!("expected one of {}", expected.join(", "))))
+  }
+
+  fn take_while(&mut self, what: &str, f: impl Fn(char) -> bool) -> ParseResult<&'a str> {
+    self.skip_whitespace();
+    let rest = self.rest();
+    let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+    if len == 0 {
+      return Err(self.error(format!("expected {what}")));
+    }
+    self.pos += len;
+    Ok(&rest[..len])
+  }
+
+  /// Run of letters, digits and underscores.
+  pub fn word(&mut self) -> ParseResult<&'a str> {
+    self.take_while("a word", is_word_char)
+  }
+
+  /// Run of anything but whitespace.
+  pub fn token(&mut self) -> ParseResult<&'a str> {
+    self.take_while("a token", |c| !c.is_whitespace())
+  }
+
+  fn number<T: FromStr>(&mut self, sign: usize) -> ParseResult<T> {
+    self.skip_whitespace();
+    let rest = self.rest();
+    let len = sign
+      + rest[sign..]
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len() - sign);
+    if len == sign {
+      return Err(self.error("expected a number"));
+    }
+    let value = rest[..len]
+      .parse()
+      .map_err(|_| self.error(format!("`{}` is out of range", &rest[..len])))?;
+    self.pos += len;
+    Ok(value)
+  }
+
+  pub fn unsigned<T: FromStr>(&mut self) -> ParseResult<T> {
+    self.number(0)
+  }
+
+  /// Integer with an optional leading `-`.
+  pub fn signed<T: FromStr>(&mut self) -> ParseResult<T> {
+    let sign = usize::from(self.peek() == Some('-'));
+    self.number(sign)
+  }
+
+  /// One or more `item`s separated by `separator`.
+  pub fn separated<T>(
+    &mut self,
+    separator: &str,
+    mut item: impl FnMut(&mut Self) -> ParseResult<T>,
+  ) -> ParseResult<Vec<T>> {
+    let mut items = vec![item(self)?];
+    while self.try_literal(separator) {
+      items.push(item(self)?);
+    }
+    Ok(items)
+  }
+}
+
+/// Runs `f` over the whole of `line`, failing if anything is left over.
+pub fn parse_line<'a, T>(
+  line: &'a str,
+  f: impl FnOnce(&mut Parser<'a>) -> ParseResult<T>,
+) -> ParseResult<T> {
+  let mut parser = Parser::new(line);
+  let value = f(&mut parser)?;
+  parser.end()?;
+  Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{parse_line, ParseError, Parser};
+
+  #[test]
+  fn numbers() {
+    let mut p = Parser::new("  12 -7 x");
+    assert_eq!(p.unsigned::<usize>(), Ok(12));
+    assert_eq!(p.signed::<isize>(), Ok(-7));
+    let err = p.signed::<isize>().unwrap_err();
+    assert_eq!(err.column, 9);
+    assert_eq!(p.rest(), "x");
+  }
+
+  #[test]
+  fn out_of_range() {
+    let err = parse_line("300", |p| p.unsigned::<u8>()).unwrap_err();
+    assert_eq!(err.message, "`300` is out of range");
+  }
+
+  #[test]
+  fn keywords() {
+    let mut p = Parser::new("move moved");
+    assert!(p.try_keyword("move"));
+    assert!(!p.try_keyword("move"));
+    assert_eq!(p.word(), Ok("moved"));
+    assert!(p.is_done());
+
+    let mut p = Parser::new("* 3");
+    assert_eq!(p.one_of(&[("+", '+'), ("*", '*')]), Ok('*'));
+    let err = p.one_of(&[("+", '+'), ("*", '*')]).unwrap_err();
+    assert_eq!(err.message, "expected one of `+`, `*`");
+  }
+
+  #[test]
+  fn lists() {
+    let items = parse_line("79, 98,1", |p| p.separated(",", Parser::unsigned::<usize>));
+    assert_eq!(items, Ok(vec![79, 98, 1]));
+    let err = parse_line("79, 98,", |p| p.separated(",", Parser::unsigned::<usize>));
+    assert_eq!(err.unwrap_err().column, 8);
+  }
+
+  #[test]
+  fn leftovers() {
+    let err = parse_line("1 2", |p| p.unsigned::<usize>()).unwrap_err();
+    assert_eq!(err.to_string(), "column 3: unexpected `2`");
+    let err = ParseError { line: None, ..err }.at_line(4);
+    assert_eq!(err.to_string(), "line 4, column 3: unexpected `2`");
+  }
+}
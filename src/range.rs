@@ -0,0 +1,318 @@
+use std::fmt::{Display, Formatter};
+
+/// Inclusive interval of integers: `min..=max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct Range {
+  pub min: isize,
+  pub max: isize,
+}
+
+impl Display for Range {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}-{}", self.min, self.max)
+  }
+}
+
+impl Range {
+  pub const fn new(min: isize, max: isize) -> Range {
+    Range { min, max }
+  }
+
+  pub const fn new_centered(center: isize, radius: isize) -> Range {
+    Range {
+      min: center - radius,
+      max: center + radius,
+    }
+  }
+
+  pub fn contains(&self, x: isize) -> bool {
+    x >= self.min && x <= self.max
+  }
+
+  pub fn contains_range(&self, other: &Self) -> bool {
+    other.min >= self.min && other.max <= self.max
+  }
+
+  pub fn overlaps(&self, other: &Self) -> bool {
+    other.min <= self.max && other.max >= self.min
+  }
+
+  /// True when both ranges overlap or sit right next to each other, so their
+  /// union is a single range.
+  pub fn touches(&self, other: &Self) -> bool {
+    other.min <= self.max.saturating_add(1) && other.max.saturating_add(1) >= self.min
+  }
+
+  pub fn len(&self) -> usize {
+    (self.max - self.min + 1) as usize
+  }
+
+  pub fn merge(&self, other: &Self) -> Option<Self> {
+    if self.overlaps(other) {
+      Some(self.hull(other))
+    } else {
+      None
+    }
+  }
+
+  pub fn intersection(&self, other: &Self) -> Option<Self> {
+    if self.overlaps(other) {
+      Some(Self {
+        min: self.min.max(other.min),
+        max: self.max.min(other.max),
+      })
+    } else {
+      None
+    }
+  }
+
+  fn hull(&self, other: &Self) -> Self {
+    Self {
+      min: self.min.min(other.min),
+      max: self.max.max(other.max),
+    }
+  }
+}
+
+impl IntoIterator for Range {
+  type Item = isize;
+  type IntoIter = std::ops::RangeInclusive<isize>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.min..=self.max
+  }
+}
+
+/// Set of integers stored as sorted, disjoint and non-adjacent ranges.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RangeSet {
+  ranges: Vec<Range>,
+}
+
+impl RangeSet {
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  pub fn ranges(&self) -> &[Range] {
+    &self.ranges
+  }
+
+  pub fn iter(&self) -> std::slice::Iter<'_, Range> {
+    self.ranges.iter()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.ranges.is_empty()
+  }
+
+  /// Number of integers covered by the set.
+  pub fn len(&self) -> usize {
+    self.ranges.iter().map(|r| r.len()).sum()
+  }
+
+  pub fn contains(&self, x: isize) -> bool {
+    self.find(x).is_some()
+  }
+
+  pub fn contains_range(&self, range: &Range) -> bool {
+    self
+      .find(range.min)
+      .is_some_and(|i| self.ranges[i].contains_range(range))
+  }
+
+  pub fn insert(&mut self, range: Range) {
+    let start = self
+      .ranges
+      .partition_point(|r| !r.touches(&range) && r.max < range.min);
+    let end = self.ranges[start..]
+      .iter()
+      .position(|r| !r.touches(&range))
+      .map_or(self.ranges.len(), |i| start + i);
+    let merged = self.ranges[start..end]
+      .iter()
+      .fold(range, |acc, r| acc.hull(r));
+    self.ranges.splice(start..end, [merged]);
+  }
+
+  pub fn union(&self, other: &Self) -> Self {
+    let mut result = self.clone();
+    for r in other.iter() {
+      result.insert(*r);
+    }
+    result
+  }
+
+  pub fn intersection(&self, other: &Self) -> Self {
+    let mut ranges = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < self.ranges.len() && j < other.ranges.len() {
+      let a = self.ranges[i];
+      let b = other.ranges[j];
+      if let Some(r) = a.intersection(&b) {
+        ranges.push(r);
+      }
+      if a.max < b.max {
+        i += 1;
+      } else {
+        j += 1;
+      }
+    }
+    Self { ranges }
+  }
+
+  pub fn difference(&self, other: &Self) -> Self {
+    let Some(bound) = self.bounds() else {
+      return Self::new();
+    };
+    self.intersection(&other.complement(bound))
+  }
+
+  /// Everything inside `bound` that is not part of the set.
+  pub fn complement(&self, bound: Range) -> Self {
+    let ranges = self.gaps_within(bound).collect();
+    Self { ranges }
+  }
+
+  /// Holes between consecutive ranges of the set.
+  pub fn gaps(&self) -> impl Iterator<Item = Range> + '_ {
+    self
+      .ranges
+      .windows(2)
+      .map(|w| Range::new(w[0].max + 1, w[1].min - 1))
+  }
+
+  /// Holes of the set inside `bound`, including the ones at both ends.
+  pub fn gaps_within(&self, bound: Range) -> impl Iterator<Item = Range> + '_ {
+    let mut next = bound.min;
+    let mut done = false;
+    let mut iter = self.ranges.iter().filter(move |r| r.overlaps(&bound));
+    std::iter::from_fn(move || {
+      while !done {
+        match iter.next() {
+          Some(r) => {
+            let gap = Range::new(next, r.min - 1);
+            next = r.max.saturating_add(1);
+            if gap.min <= gap.max {
+              return Some(gap);
+            }
+          }
+          None => {
+            done = true;
+            if next <= bound.max {
+              return Some(Range::new(next, bound.max));
+            }
+          }
+        }
+      }
+      None
+    })
+  }
+
+  /// Smallest range holding every element of the set.
+  pub fn bounds(&self) -> Option<Range> {
+    let first = self.ranges.first()?;
+    let last = self.ranges.last()?;
+    Some(Range::new(first.min, last.max))
+  }
+
+  fn find(&self, x: isize) -> Option<usize> {
+    let i = self.ranges.partition_point(|r| r.max < x);
+    self.ranges.get(i).filter(|r| r.contains(x)).map(|_| i)
+  }
+}
+
+impl FromIterator<Range> for RangeSet {
+  fn from_iter<I: IntoIterator<Item = Range>>(iter: I) -> Self {
+    let mut set = Self::new();
+    for r in iter {
+      set.insert(r);
+    }
+    set
+  }
+}
+
+impl From<Range> for RangeSet {
+  fn from(r: Range) -> Self {
+    Self { ranges: vec![r] }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Range, RangeSet};
+
+  fn set(ranges: &[(isize, isize)]) -> RangeSet {
+    ranges.iter().map(|&(a, b)| Range::new(a, b)).collect()
+  }
+
+  #[test]
+  fn range() {
+    let r = Range::new(2, 8);
+    assert!(r.contains(2));
+    assert!(!r.contains(9));
+    assert!(r.contains_range(&Range::new(3, 7)));
+    assert!(!r.contains_range(&Range::new(3, 9)));
+    assert!(r.overlaps(&Range::new(8, 9)));
+    assert!(!r.overlaps(&Range::new(9, 9)));
+    assert!(r.touches(&Range::new(9, 9)));
+    assert_eq!(r.len(), 7);
+    assert_eq!(Range::new_centered(0, 2), Range::new(-2, 2));
+    assert_eq!(r.into_iter().count(), 7);
+  }
+
+  #[test]
+  fn join() {
+    let r1 = Range::new(0, 6);
+    let r2 = Range::new(3, 9);
+    assert_eq!(r1.merge(&r2), Some(Range::new(0, 9)));
+    assert_eq!(r1.merge(&Range::new(7, 9)), None);
+    assert_eq!(r1.intersection(&r2), Some(Range::new(3, 6)));
+  }
+
+  #[test]
+  fn insert() {
+    let mut s = set(&[(10, 12), (0, 2), (5, 6)]);
+    assert_eq!(
+      s.ranges(),
+      &[Range::new(0, 2), Range::new(5, 6), Range::new(10, 12)]
+    );
+    s.insert(Range::new(3, 4));
+    assert_eq!(s.ranges(), &[Range::new(0, 6), Range::new(10, 12)]);
+    s.insert(Range::new(-5, 20));
+    assert_eq!(s.ranges(), &[Range::new(-5, 20)]);
+    assert_eq!(s.len(), 26);
+    assert!(s.contains(-5));
+    assert!(!s.contains(21));
+  }
+
+  #[test]
+  fn algebra() {
+    let a = set(&[(0, 5), (10, 15)]);
+    let b = set(&[(3, 12)]);
+    assert_eq!(a.union(&b), set(&[(0, 15)]));
+    assert_eq!(a.intersection(&b), set(&[(3, 5), (10, 12)]));
+    assert_eq!(a.difference(&b), set(&[(0, 2), (13, 15)]));
+    assert_eq!(b.difference(&a), set(&[(6, 9)]));
+    assert_eq!(
+      a.complement(Range::new(-2, 20)),
+      set(&[(-2, -1), (6, 9), (16, 20)])
+    );
+    assert_eq!(a.complement(Range::new(1, 4)), RangeSet::new());
+    assert!(a.contains_range(&Range::new(11, 14)));
+    assert!(!a.contains_range(&Range::new(4, 11)));
+  }
+
+  #[test]
+  fn gaps() {
+    let a = set(&[(0, 5), (10, 15), (17, 17)]);
+    let gaps: Vec<Range> = a.gaps().collect();
+    assert_eq!(gaps, vec![Range::new(6, 9), Range::new(16, 16)]);
+    let gaps: Vec<Range> = a.gaps_within(Range::new(3, 20)).collect();
+    assert_eq!(
+      gaps,
+      vec![Range::new(6, 9), Range::new(16, 16), Range::new(18, 20)]
+    );
+    assert_eq!(a.bounds(), Some(Range::new(0, 17)));
+  }
+}
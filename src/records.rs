@@ -0,0 +1,183 @@
+use std::fmt::{Display, Formatter};
+
+/// Consecutive lines of the input, `line` being the 1-based number of the
+/// first one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Record<'a> {
+  pub line: usize,
+  pub lines: &'a [String],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordError {
+  pub line: usize,
+  pub message: String,
+}
+
+impl Display for RecordError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "line {}: {}", self.line, self.message)
+  }
+}
+
+impl std::error::Error for RecordError {}
+
+fn is_blank(line: &str) -> bool {
+  line.trim().is_empty()
+}
+
+fn skip_blank(lines: &[String], mut pos: usize) -> usize {
+  while pos < lines.len() && is_blank(&lines[pos]) {
+    pos += 1;
+  }
+  pos
+}
+
+fn end_of_paragraph(lines: &[String], mut pos: usize) -> usize {
+  while pos < lines.len() && !is_blank(&lines[pos]) {
+    pos += 1;
+  }
+  pos
+}
+
+/// Groups of lines separated by one or more blank lines.
+pub struct Paragraphs<'a> {
+  lines: &'a [String],
+  pos: usize,
+}
+
+pub fn paragraphs(lines: &[String]) -> Paragraphs<'_> {
+  Paragraphs { lines, pos: 0 }
+}
+
+impl<'a> Iterator for Paragraphs<'a> {
+  type Item = Record<'a>;
+
+  fn next(&mut self) -> Option<Record<'a>> {
+    let start = skip_blank(self.lines, self.pos);
+    if start == self.lines.len() {
+      return None;
+    }
+    self.pos = end_of_paragraph(self.lines, start);
+    Some(Record {
+      line: start + 1,
+      lines: &self.lines[start..self.pos],
+    })
+  }
+}
+
+/// Non-blank lines taken `size` at a time. Blank lines may only sit between
+/// chunks; a chunk cut short by one, or by the end of the input, is an error
+/// and ends the iteration.
+pub struct Chunks<'a> {
+  lines: &'a [String],
+  pos: usize,
+  size: usize,
+}
+
+pub fn chunks(lines: &[String], size: usize) -> Chunks<'_> {
+  assert!(size > 0);
+  Chunks {
+    lines,
+    pos: 0,
+    size,
+  }
+}
+
+impl<'a> Iterator for Chunks<'a> {
+  type Item = Result<Record<'a>, RecordError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let start = skip_blank(self.lines, self.pos);
+    if start == self.lines.len() {
+      return None;
+    }
+    let end = end_of_paragraph(self.lines, start).min(start + self.size);
+    if end - start < self.size {
+      self.pos = self.lines.len();
+      return Some(Err(RecordError {
+        line: start + 1,
+        message: format!("group of {} lines, expected {}", end - start, self.size),
+      }));
+    }
+    self.pos = end;
+    Some(Ok(Record {
+      line: start + 1,
+      lines: &self.lines[start..end],
+    }))
+  }
+}
+
+/// Splits the input at its first blank line, as in inputs made of a header
+/// followed by a body. Both parts must hold at least one line.
+pub fn sections(lines: &[String]) -> Result<(Record<'_>, Record<'_>), RecordError> {
+  let start = skip_blank(lines, 0);
+  let split = end_of_paragraph(lines, start);
+  let body_start = skip_blank(lines, split);
+  let missing = |what: &str, line: usize| RecordError {
+    line,
+    message: format!("missing {what}"),
+  };
+  if start == lines.len() {
+    return Err(missing("header", 1));
+  }
+  if body_start == lines.len() {
+    return Err(missing("body after the header", split + 1));
+  }
+  let header = Record {
+    line: start + 1,
+    lines: &lines[start..split],
+  };
+  let body = Record {
+    line: body_start + 1,
+    lines: &lines[body_start..],
+  };
+  Ok((header, body))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{chunks, paragraphs, sections, Record, RecordError};
+
+  fn lines(s: &str) -> Vec<String> {
+    s.lines().map(|l| l.to_owned()).collect()
+  }
+
+  fn starts(records: &[Record]) -> Vec<(usize, usize)> {
+    records.iter().map(|r| (r.line, r.lines.len())).collect()
+  }
+
+  #[test]
+  fn paragraph_groups() {
+    let input = lines("\n1\n2\n\n\n3\n  \n4\n5\n6\n");
+    let found: Vec<Record> = paragraphs(&input).collect();
+    assert_eq!(starts(&found), vec![(2, 2), (6, 1), (8, 3)]);
+    assert_eq!(found[2].lines, &["4", "5", "6"]);
+    assert_eq!(paragraphs(&lines("\n\n")).count(), 0);
+  }
+
+  #[test]
+  fn fixed_chunks() {
+    let input = lines("a\nb\nc\nd\n\ne\nf\n");
+    let found: Vec<Record> = chunks(&input, 2).map(Result::unwrap).collect();
+    assert_eq!(starts(&found), vec![(1, 2), (3, 2), (6, 2)]);
+
+    let input = lines("a\nb\n\nc\n\nd\ne\n");
+    let found: Vec<Result<Record, RecordError>> = chunks(&input, 2).collect();
+    assert_eq!(found.len(), 2);
+    let err = found[1].clone().unwrap_err();
+    assert_eq!(err.to_string(), "line 4: group of 1 lines, expected 2");
+  }
+
+  #[test]
+  fn header_and_body() {
+    let input = lines("    [D]\n 1   2\n\nmove 1 from 2 to 1\nmove 3 from 1 to 3\n");
+    let (header, body) = sections(&input).unwrap();
+    assert_eq!((header.line, header.lines.len()), (1, 2));
+    assert_eq!((body.line, body.lines.len()), (4, 2));
+
+    let err = sections(&lines("a\nb\n\n")).unwrap_err();
+    assert_eq!(err.to_string(), "line 3: missing body after the header");
+    assert_eq!(sections(&[]).unwrap_err().line, 1);
+  }
+}
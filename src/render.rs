@@ -0,0 +1,472 @@
+use crate::simulation::Simulation;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+pub type Rgb = [u8; 3];
+
+pub const BLACK: Rgb = [0, 0, 0];
+pub const WHITE: Rgb = [255, 255, 255];
+
+/// Glyphs and colours of the pixel values a `Frame` can hold; the value of a
+/// pixel is the position of its entry. The first entry is the background.
+#[derive(Debug, Clone)]
+pub struct Palette {
+  glyphs: Vec<char>,
+  colors: Vec<Rgb>,
+}
+
+impl Palette {
+  pub fn new(entries: &[(char, Rgb)]) -> Self {
+    assert!(
+      !entries.is_empty() && entries.len() <= 256,
+      "a palette holds 1 to 256 entries"
+    );
+    Self {
+      glyphs: entries.iter().map(|&(c, _)| c).collect(),
+      colors: entries.iter().map(|&(_, rgb)| rgb).collect(),
+    }
+  }
+
+  pub fn len(&self) -> usize {
+    self.glyphs.len()
+  }
+
+  /// Value of glyph `c`; unknown glyphs map to the background.
+  pub fn index(&self, c: char) -> u8 {
+    self.glyphs.iter().position(|&g| g == c).unwrap_or(0) as u8
+  }
+
+  pub fn glyph(&self, value: u8) -> char {
+    self.glyphs[value as usize]
+  }
+
+  pub fn color(&self, value: u8) -> Rgb {
+    self.colors[value as usize]
+  }
+
+  fn gray(&self, value: u8) -> u8 {
+    let [r, g, b] = self.color(value).map(|c| c as u32);
+    ((r * 299 + g * 587 + b * 114) / 1000) as u8
+  }
+}
+
+/// Where a frame sits when it is padded to a larger size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+  TopLeft,
+  BottomLeft,
+}
+
+/// Rectangle of pixel values, indices into a `Palette`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+  width: usize,
+  height: usize,
+  pixels: Vec<u8>,
+}
+
+impl Frame {
+  pub fn new(width: usize, height: usize) -> Self {
+    Self {
+      width,
+      height,
+      pixels: vec![0; width * height],
+    }
+  }
+
+  pub fn width(&self) -> usize {
+    self.width
+  }
+
+  pub fn height(&self) -> usize {
+    self.height
+  }
+
+  pub fn get(&self, x: usize, y: usize) -> u8 {
+    self.pixels[y * self.width + x]
+  }
+
+  pub fn set(&mut self, x: usize, y: usize, value: u8) {
+    self.pixels[y * self.width + x] = value;
+  }
+
+  /// Reads a text grid such as `Simulation::render` produces. Short lines are
+  /// padded with the background.
+  pub fn from_text(text: &str, palette: &Palette) -> Self {
+    let lines: Vec<&str> = text.lines().collect();
+    let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let mut frame = Self::new(width, lines.len());
+    for (y, line) in lines.iter().enumerate() {
+      for (x, c) in line.chars().enumerate() {
+        frame.set(x, y, palette.index(c));
+      }
+    }
+    frame
+  }
+
+  /// Sparse grid: the smallest frame holding every point, `y` growing
+  /// downwards. Points given twice keep their last value.
+  pub fn from_points(points: impl IntoIterator<Item = ((isize, isize), u8)>) -> Self {
+    let points: Vec<((isize, isize), u8)> = points.into_iter().collect();
+    if points.is_empty() {
+      return Self::new(0, 0);
+    }
+    let min_x = points.iter().map(|((x, _), _)| *x).min().unwrap();
+    let max_x = points.iter().map(|((x, _), _)| *x).max().unwrap();
+    let min_y = points.iter().map(|((_, y), _)| *y).min().unwrap();
+    let max_y = points.iter().map(|((_, y), _)| *y).max().unwrap();
+    let mut frame = Self::new((max_x - min_x + 1) as usize, (max_y - min_y + 1) as usize);
+    for ((x, y), value) in points {
+      frame.set((x - min_x) as usize, (y - min_y) as usize, value);
+    }
+    frame
+  }
+
+  pub fn to_text(&self, palette: &Palette) -> String {
+    let mut text = String::with_capacity((self.width + 1) * self.height);
+    for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+      text.extend(row.iter().map(|&v| palette.glyph(v)));
+      text.push('\n');
+    }
+    text
+  }
+
+  /// Every pixel blown up to a `scale` × `scale` square.
+  pub fn scaled(&self, scale: usize) -> Self {
+    let mut frame = Self::new(self.width * scale, self.height * scale);
+    for y in 0..frame.height {
+      for x in 0..frame.width {
+        frame.set(x, y, self.get(x / scale, y / scale));
+      }
+    }
+    frame
+  }
+
+  /// Copy grown to `width` × `height` with background pixels.
+  pub fn padded(&self, width: usize, height: usize, anchor: Anchor) -> Self {
+    assert!(width >= self.width && height >= self.height);
+    let top = match anchor {
+      Anchor::TopLeft => 0,
+      Anchor::BottomLeft => height - self.height,
+    };
+    let mut frame = Self::new(width, height);
+    for y in 0..self.height {
+      for x in 0..self.width {
+        frame.set(x, top + y, self.get(x, y));
+      }
+    }
+    frame
+  }
+
+  /// Binary PPM (P6).
+  pub fn write_ppm(&self, w: &mut impl Write, palette: &Palette) -> io::Result<()> {
+    write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+    let bytes: Vec<u8> = self.pixels.iter().flat_map(|&v| palette.color(v)).collect();
+    w.write_all(&bytes)
+  }
+
+  /// Binary PGM (P5), every colour turned to its luminance.
+  pub fn write_pgm(&self, w: &mut impl Write, palette: &Palette) -> io::Result<()> {
+    write!(w, "P5\n{} {}\n255\n", self.width, self.height)?;
+    let bytes: Vec<u8> = self.pixels.iter().map(|&v| palette.gray(v)).collect();
+    w.write_all(&bytes)
+  }
+
+  pub fn save_ppm(&self, path: impl AsRef<Path>, palette: &Palette) -> io::Result<()> {
+    let mut w = create(path.as_ref())?;
+    self.write_ppm(&mut w, palette)?;
+    w.flush()
+  }
+
+  pub fn save_pgm(&self, path: impl AsRef<Path>, palette: &Palette) -> io::Result<()> {
+    let mut w = create(path.as_ref())?;
+    self.write_pgm(&mut w, palette)?;
+    w.flush()
+  }
+}
+
+fn create(path: &Path) -> io::Result<BufWriter<File>> {
+  if let Some(dir) = path.parent() {
+    fs::create_dir_all(dir)?;
+  }
+  Ok(BufWriter::new(File::create(path)?))
+}
+
+/// Frames of `simulation` every `every` steps, from its current state until
+/// it finishes or `limit` steps went by.
+pub fn record(
+  simulation: &mut impl Simulation,
+  every: usize,
+  limit: usize,
+  palette: &Palette,
+) -> Vec<Frame> {
+  let mut frames = vec![Frame::from_text(&simulation.render(), palette)];
+  let mut steps = 0;
+  while steps < limit && !simulation.is_finished() {
+    steps += simulation.run_for(every.min(limit - steps));
+    frames.push(Frame::from_text(&simulation.render(), palette));
+  }
+  frames
+}
+
+/// Writes `frame_0000.ppm`, `frame_0001.ppm`, ... into `dir`.
+pub fn save_numbered(dir: impl AsRef<Path>, frames: &[Frame], palette: &Palette) -> io::Result<()> {
+  for (i, frame) in frames.iter().enumerate() {
+    frame.save_ppm(dir.as_ref().join(format!("frame_{i:04}.ppm")), palette)?;
+  }
+  Ok(())
+}
+
+/// Looping animated GIF, showing each frame for `delay` hundredths of a
+/// second. Frames smaller than the largest one are padded around `anchor`.
+pub fn write_gif(
+  w: &mut impl Write,
+  frames: &[Frame],
+  palette: &Palette,
+  delay: u16,
+  anchor: Anchor,
+) -> io::Result<()> {
+  let width = frames.iter().map(|f| f.width).max().unwrap_or(0);
+  let height = frames.iter().map(|f| f.height).max().unwrap_or(0);
+  let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "frame too large for a GIF");
+  let width16 = u16::try_from(width).map_err(|_| too_large())?;
+  let height16 = u16::try_from(height).map_err(|_| too_large())?;
+  // the colour table holds 2^table_bits entries, at least 2
+  let table_bits = (1..=8).find(|b| 1 << b >= palette.len()).unwrap();
+
+  w.write_all(b"GIF89a")?;
+  w.write_all(&width16.to_le_bytes())?;
+  w.write_all(&height16.to_le_bytes())?;
+  w.write_all(&[0xF0 | (table_bits - 1) as u8, 0, 0])?;
+  for i in 0..1 << table_bits {
+    let color = if i < palette.len() {
+      palette.color(i as u8)
+    } else {
+      BLACK
+    };
+    w.write_all(&color)?;
+  }
+  // loop forever
+  w.write_all(b"\x21\xFF\x0BNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+
+  let min_code_size = table_bits.max(2) as u8;
+  for frame in frames {
+    let frame = frame.padded(width, height, anchor);
+    w.write_all(&[0x21, 0xF9, 0x04, 0x00])?;
+    w.write_all(&delay.to_le_bytes())?;
+    w.write_all(&[0x00, 0x00])?;
+    w.write_all(&[0x2C, 0, 0, 0, 0])?;
+    w.write_all(&width16.to_le_bytes())?;
+    w.write_all(&height16.to_le_bytes())?;
+    w.write_all(&[0x00, min_code_size])?;
+    for block in lzw_encode(min_code_size, &frame.pixels).chunks(255) {
+      w.write_all(&[block.len() as u8])?;
+      w.write_all(block)?;
+    }
+    w.write_all(&[0x00])?;
+  }
+  w.write_all(&[0x3B])
+}
+
+pub fn save_gif(
+  path: impl AsRef<Path>,
+  frames: &[Frame],
+  palette: &Palette,
+  delay: u16,
+  anchor: Anchor,
+) -> io::Result<()> {
+  let mut w = create(path.as_ref())?;
+  write_gif(&mut w, frames, palette, delay, anchor)?;
+  w.flush()
+}
+
+const MAX_CODES: u16 = 4096;
+
+// Variable-width LZW as GIF wants it: codes packed least significant bit
+// first, growing a bit whenever the table outgrows the current width, and the
+// table restarted with a clear code once it holds 4096 entries.
+fn lzw_encode(min_code_size: u8, data: &[u8]) -> Vec<u8> {
+  let clear: u16 = 1 << min_code_size;
+  let end = clear + 1;
+  let mut out = BitWriter::default();
+  let mut size = min_code_size + 1;
+  let mut next = end + 1;
+  let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+
+  out.write(clear, size);
+  let mut prefix: Option<u16> = None;
+  for &byte in data {
+    let Some(p) = prefix else {
+      prefix = Some(byte as u16);
+      continue;
+    };
+    if let Some(&code) = table.get(&(p, byte)) {
+      prefix = Some(code);
+      continue;
+    }
+    out.write(p, size);
+    if next == MAX_CODES {
+      out.write(clear, size);
+      table.clear();
+      size = min_code_size + 1;
+      next = end + 1;
+    } else {
+      table.insert((p, byte), next);
+      if next == 1 << size {
+        size += 1;
+      }
+      next += 1;
+    }
+    prefix = Some(byte as u16);
+  }
+  if let Some(p) = prefix {
+    out.write(p, size);
+  }
+  out.write(end, size);
+  out.finish()
+}
+
+#[derive(Default)]
+struct BitWriter {
+  bytes: Vec<u8>,
+  buffer: u32,
+  bits: u8,
+}
+
+impl BitWriter {
+  fn write(&mut self, code: u16, size: u8) {
+    self.buffer |= (code as u32) << self.bits;
+    self.bits += size;
+    while self.bits >= 8 {
+      self.bytes.push(self.buffer as u8);
+      self.buffer >>= 8;
+      self.bits -= 8;
+    }
+  }
+
+  fn finish(mut self) -> Vec<u8> {
+    if self.bits > 0 {
+      self.bytes.push(self.buffer as u8);
+    }
+    self.bytes
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{lzw_encode, write_gif, Anchor, Frame, Palette, BLACK, WHITE};
+  use indoc::indoc;
+
+  fn palette() -> Palette {
+    Palette::new(&[('.', BLACK), ('#', WHITE), ('o', [200, 150, 0])])
+  }
+
+  // Reference decoder, reading codes the way GIF viewers do.
+  fn lzw_decode(min_code_size: u8, data: &[u8]) -> Vec<u8> {
+    let clear = 1usize << min_code_size;
+    let end = clear + 1;
+    let mut table: Vec<Vec<u8>> = vec![];
+    let mut size = min_code_size + 1;
+    let mut previous: Option<Vec<u8>> = None;
+    let mut out = vec![];
+    let (mut buffer, mut bits, mut pos) = (0u32, 0u8, 0);
+    loop {
+      while bits < size {
+        buffer |= (data[pos] as u32) << bits;
+        pos += 1;
+        bits += 8;
+      }
+      let code = (buffer & ((1 << size) - 1)) as usize;
+      buffer >>= size;
+      bits -= size;
+      if code == clear {
+        table = (0..clear).map(|i| vec![i as u8]).collect();
+        table.extend([vec![], vec![]]);
+        size = min_code_size + 1;
+        previous = None;
+        continue;
+      }
+      if code == end {
+        return out;
+      }
+      let entry = match (table.get(code), &previous) {
+        (Some(entry), _) => entry.clone(),
+        (None, Some(p)) => [p.clone(), vec![p[0]]].concat(),
+        (None, None) => panic!("invalid code"),
+      };
+      if let Some(p) = previous {
+        table.push([p, vec![entry[0]]].concat());
+        if table.len() == 1 << size && size < 12 {
+          size += 1;
+        }
+      }
+      out.extend(&entry);
+      previous = Some(entry);
+    }
+  }
+
+  #[test]
+  fn text_round_trip() {
+    let text = indoc! {"
+      ..#.
+      .o#
+      ####
+    "};
+    let frame = Frame::from_text(text, &palette());
+    assert_eq!((frame.width(), frame.height()), (4, 3));
+    assert_eq!(frame.get(1, 1), 2);
+    assert_eq!(frame.to_text(&palette()), "..#.\n.o#.\n####\n");
+    let scaled = frame.scaled(2);
+    assert_eq!(scaled.to_text(&palette()).lines().nth(3), Some("..oo##.."));
+  }
+
+  #[test]
+  fn sparse() {
+    let frame = Frame::from_points([((-1, 5), 1), ((1, 6), 2)]);
+    assert_eq!(frame.to_text(&palette()), "#..\n..o\n");
+    let padded = frame.padded(4, 3, Anchor::BottomLeft);
+    assert_eq!(padded.to_text(&palette()), "....\n#...\n..o.\n");
+  }
+
+  #[test]
+  fn netpbm() {
+    let frame = Frame::from_text("#o\n", &palette());
+    let mut ppm = vec![];
+    frame.write_ppm(&mut ppm, &palette()).unwrap();
+    assert_eq!(ppm, b"P6\n2 1\n255\n\xFF\xFF\xFF\xC8\x96\x00");
+    let mut pgm = vec![];
+    frame.write_pgm(&mut pgm, &palette()).unwrap();
+    assert_eq!(pgm, b"P5\n2 1\n255\n\xFF\x93");
+  }
+
+  #[test]
+  fn lzw() {
+    let inputs: Vec<Vec<u8>> = vec![
+      vec![],
+      vec![1],
+      vec![0, 0, 0, 0, 0, 0, 0, 0],
+      (0..10_000).map(|i| (i * i % 7 % 3) as u8).collect(),
+      (0..100_000u32)
+        .map(|i| (i.wrapping_mul(2654435761) >> 28) as u8)
+        .collect(),
+    ];
+    for (input, min_code_size) in inputs.iter().zip([2, 2, 2, 2, 4]) {
+      let encoded = lzw_encode(min_code_size, input);
+      assert_eq!(&lzw_decode(min_code_size, &encoded), input);
+    }
+  }
+
+  #[test]
+  fn gif() {
+    let frames = [
+      Frame::from_text("#.\n", &palette()),
+      Frame::from_text("o\n#\n", &palette()),
+    ];
+    let mut gif = vec![];
+    write_gif(&mut gif, &frames, &palette(), 10, Anchor::TopLeft).unwrap();
+    assert!(gif.starts_with(b"GIF89a\x02\x00\x02\x00\xF1"));
+    assert_eq!(gif.last(), Some(&0x3B));
+  }
+}
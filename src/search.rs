@@ -0,0 +1,221 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchResult<S> {
+  pub distance: usize,
+  pub path: Vec<S>,
+}
+
+/// Keeps every discovered state once, together with the index of the state it
+/// was reached from, so paths can be rebuilt without cloning them around.
+struct Explored<S> {
+  states: Vec<S>,
+  parents: Vec<Option<usize>>,
+  index: HashMap<S, usize>,
+}
+
+impl<S: Clone + Eq + Hash> Explored<S> {
+  fn new() -> Self {
+    Self {
+      states: vec![],
+      parents: vec![],
+      index: HashMap::new(),
+    }
+  }
+
+  fn get(&self, state: &S) -> Option<usize> {
+    self.index.get(state).copied()
+  }
+
+  fn add(&mut self, state: S, parent: Option<usize>) -> usize {
+    let id = self.states.len();
+    self.index.insert(state.clone(), id);
+    self.states.push(state);
+    self.parents.push(parent);
+    id
+  }
+
+  fn path_to(&self, id: usize) -> Vec<S> {
+    let mut path = vec![];
+    let mut current = Some(id);
+    while let Some(i) = current {
+      path.push(self.states[i].clone());
+      current = self.parents[i];
+    }
+    path.reverse();
+    path
+  }
+}
+
+/// Breadth-first search over unweighted edges, starting from every state in
+/// `starts` at distance zero.
+pub fn bfs<S, I, N, NI, G>(starts: I, mut neighbours: N, mut is_goal: G) -> Option<SearchResult<S>>
+where
+  S: Clone + Eq + Hash,
+  I: IntoIterator<Item = S>,
+  N: FnMut(&S) -> NI,
+  NI: IntoIterator<Item = S>,
+  G: FnMut(&S) -> bool,
+{
+  let mut explored = Explored::new();
+  let mut open: VecDeque<(usize, usize)> = VecDeque::new();
+  for start in starts {
+    if explored.get(&start).is_none() {
+      let id = explored.add(start, None);
+      open.push_back((id, 0));
+    }
+  }
+
+  while let Some((id, distance)) = open.pop_front() {
+    let state = explored.states[id].clone();
+    if is_goal(&state) {
+      return Some(SearchResult {
+        distance,
+        path: explored.path_to(id),
+      });
+    }
+    for next in neighbours(&state) {
+      if explored.get(&next).is_none() {
+        let next_id = explored.add(next, Some(id));
+        open.push_back((next_id, distance + 1));
+      }
+    }
+  }
+  None
+}
+
+/// Shortest path over weighted edges. `neighbours` yields `(state, cost)`.
+pub fn dijkstra<S, I, N, NI, G>(starts: I, neighbours: N, is_goal: G) -> Option<SearchResult<S>>
+where
+  S: Clone + Eq + Hash,
+  I: IntoIterator<Item = S>,
+  N: FnMut(&S) -> NI,
+  NI: IntoIterator<Item = (S, usize)>,
+  G: FnMut(&S) -> bool,
+{
+  astar(starts, neighbours, |_| 0, is_goal)
+}
+
+/// A* search. `heuristic` must never overestimate the remaining cost for the
+/// returned path to be the shortest one.
+pub fn astar<S, I, N, NI, H, G>(
+  starts: I,
+  mut neighbours: N,
+  mut heuristic: H,
+  mut is_goal: G,
+) -> Option<SearchResult<S>>
+where
+  S: Clone + Eq + Hash,
+  I: IntoIterator<Item = S>,
+  N: FnMut(&S) -> NI,
+  NI: IntoIterator<Item = (S, usize)>,
+  H: FnMut(&S) -> usize,
+  G: FnMut(&S) -> bool,
+{
+  let mut explored = Explored::new();
+  let mut costs: Vec<usize> = vec![];
+  let mut open: BinaryHeap<Reverse<(usize, usize, usize)>> = BinaryHeap::new();
+  for start in starts {
+    if explored.get(&start).is_none() {
+      let estimate = heuristic(&start);
+      let id = explored.add(start, None);
+      costs.push(0);
+      open.push(Reverse((estimate, 0, id)));
+    }
+  }
+
+  while let Some(Reverse((_, cost, id))) = open.pop() {
+    if cost > costs[id] {
+      continue;
+    }
+    let state = explored.states[id].clone();
+    if is_goal(&state) {
+      return Some(SearchResult {
+        distance: cost,
+        path: explored.path_to(id),
+      });
+    }
+    for (next, step) in neighbours(&state) {
+      let next_cost = cost + step;
+      let next_id = match explored.get(&next) {
+        Some(next_id) if costs[next_id] <= next_cost => continue,
+        Some(next_id) => {
+          costs[next_id] = next_cost;
+          explored.parents[next_id] = Some(id);
+          next_id
+        }
+        None => {
+          costs.push(next_cost);
+          explored.add(next.clone(), Some(id))
+        }
+      };
+      open.push(Reverse((next_cost + heuristic(&next), next_cost, next_id)));
+    }
+  }
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{astar, bfs, dijkstra};
+
+  fn line_neighbours(x: &isize) -> Vec<isize> {
+    vec![x - 1, x + 1]
+  }
+
+  #[test]
+  fn bfs_path() {
+    let result = bfs([0], line_neighbours, |&x| x == 3).unwrap();
+    assert_eq!(result.distance, 3);
+    assert_eq!(result.path, vec![0, 1, 2, 3]);
+  }
+
+  #[test]
+  fn bfs_multi_source() {
+    let result = bfs([0, 10], line_neighbours, |&x| x == 8).unwrap();
+    assert_eq!(result.distance, 2);
+    assert_eq!(result.path, vec![10, 9, 8]);
+  }
+
+  #[test]
+  fn bfs_unreachable() {
+    let result = bfs(
+      [0],
+      |&x: &isize| if x < 5 { vec![x + 1] } else { vec![] },
+      |&x| x == 6,
+    );
+    assert_eq!(result, None);
+  }
+
+  #[test]
+  fn dijkstra_weights() {
+    // 0 -> 1 is expensive, going around through 2 is cheaper
+    let edges = |x: &usize| match x {
+      0 => vec![(1, 10), (2, 1)],
+      2 => vec![(3, 1)],
+      3 => vec![(1, 1)],
+      _ => vec![],
+    };
+    let result = dijkstra([0], edges, |&x| x == 1).unwrap();
+    assert_eq!(result.distance, 3);
+    assert_eq!(result.path, vec![0, 2, 3, 1]);
+  }
+
+  #[test]
+  fn astar_grid() {
+    let neighbours = |&(x, y): &(isize, isize)| {
+      [(1, 0), (-1, 0), (0, 1), (0, -1)]
+        .into_iter()
+        .map(move |(dx, dy)| ((x + dx, y + dy), 1))
+        .filter(|((x, y), _)| (0..5).contains(x) && (0..5).contains(y) && !(*x == 2 && *y < 4))
+    };
+    let heuristic = |&(x, y): &(isize, isize)| ((4 - x).abs() + y.abs()) as usize;
+    let result = astar([(0, 0)], neighbours, heuristic, |&p| p == (4, 0)).unwrap();
+    assert_eq!(result.distance, 12);
+    assert_eq!(result.path.len(), 13);
+    assert_eq!(result.path.first(), Some(&(0, 0)));
+    assert_eq!(result.path.last(), Some(&(4, 0)));
+  }
+}
@@ -1,8 +1,7 @@
-use std::cell::{Ref, RefCell, RefMut};
-use std::collections::HashMap;
+use crate::parser::{parse_line, ParseResult, Parser};
+use crate::tree::Tree;
 use std::fs::File;
 use std::io::{prelude::*, BufReader};
-use std::rc::Rc;
 
 type Input = Vec<String>;
 
@@ -15,106 +14,67 @@ enum ConsoleLine {
 }
 
 impl ConsoleLine {
-  fn from_line(l: &String) -> Self {
-    let chunks: Vec<&str> = l.split(' ').collect();
-    match chunks[0] {
-      "$" => match chunks[1] {
-        "cd" => ConsoleLine::CD(chunks[2].to_owned()),
-        "ls" => ConsoleLine::LS,
-        _ => unreachable!(),
-      },
-      "dir" => ConsoleLine::Directory(chunks[1].to_owned()),
-      size => {
-        let s: usize = size.parse().unwrap();
-        let name: String = chunks[1].to_owned();
-        ConsoleLine::File(name, s)
+  fn parse(p: &mut Parser) -> ParseResult<Self> {
+    if p.try_literal("$") {
+      if p.try_keyword("cd") {
+        return Ok(ConsoleLine::CD(p.token()?.to_owned()));
       }
+      p.keyword("ls")?;
+      return Ok(ConsoleLine::LS);
     }
+    if p.try_keyword("dir") {
+      return Ok(ConsoleLine::Directory(p.token()?.to_owned()));
+    }
+    let size: usize = p.unsigned()?;
+    let name = p.token()?.to_owned();
+    Ok(ConsoleLine::File(name, size))
   }
-}
 
-type NodePointer = Rc<RefCell<Node>>;
+  fn from_line(l: &str) -> Self {
+    parse_line(l, ConsoleLine::parse).unwrap()
+  }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum NodeType {
+enum Entry {
   Folder,
-  File,
+  File(usize),
 }
 
-struct Node {
-  parent: Option<NodePointer>,
-  size: Option<usize>,
-  name: String,
-  node_type: NodeType,
-  children: HashMap<String, NodePointer>,
-}
+type FileSystem = Tree<Entry>;
 
-impl Node {
-  fn create_root() -> Self {
-    Self {
-      parent: None,
-      size: None,
-      name: "/".to_owned(),
-      node_type: NodeType::Folder,
-      children: Default::default(),
+fn build_tree(input: &Input) -> FileSystem {
+  let mut tree = Tree::new("/", Entry::Folder);
+  let mut current = tree.root();
+  for cmd_line in input {
+    match ConsoleLine::from_line(cmd_line) {
+      ConsoleLine::CD(target) => {
+        current = tree
+          .resolve(current, &target)
+          .unwrap_or_else(|| panic!("cd into unknown folder {target}"));
+      }
+      ConsoleLine::Directory(dir_name) => {
+        tree.add_child(current, &dir_name, Entry::Folder);
+      }
+      ConsoleLine::File(file_name, size) => {
+        tree.add_child(current, &file_name, Entry::File(size));
+      }
+      ConsoleLine::LS => {}
     }
   }
+  tree
 }
 
-fn print_path(node: &NodePointer) {
-  let mut path: Vec<String> = vec![];
-  let mut current = node.clone();
-  path.push(current.borrow().name.clone());
-  let mut hasParent = current.borrow().parent.is_some();
-  while hasParent {
-    current = {
-      let current_node = current.borrow();
-      let p = current_node.parent.as_ref().unwrap();
-      path.push(p.borrow().name.clone());
-      p.clone()
+// Total size below every node, indexed by node id.
+fn calculate_sizes(tree: &FileSystem) -> Vec<usize> {
+  let mut sizes = vec![0; tree.len()];
+  for id in tree.post_order(tree.root()) {
+    sizes[id.index()] = match tree.get(id) {
+      Entry::File(size) => *size,
+      Entry::Folder => tree.children(id).iter().map(|c| sizes[c.index()]).sum(),
     };
-    hasParent = current.borrow().parent.is_some();
   }
-
-  let path_str: String = path
-    .iter()
-    .rev()
-    .map(|s| s.to_owned())
-    .collect::<Vec<String>>()
-    .join("/");
-  println!("{path_str}")
-}
-
-fn calculate_folder_size(np: &NodePointer) {
-  let node: Ref<Node> = np.borrow();
-  let total: usize = node
-    .children
-    .iter()
-    .map(|(_k, v)| {
-      let size_opt = { v.borrow().size };
-      if size_opt.is_none() {
-        calculate_folder_size(&v)
-      }
-      let size = { v.borrow().size.unwrap() };
-      size
-    })
-    .sum();
-  drop(node);
-  let mut node: RefMut<Node> = np.borrow_mut();
-  node.size = Some(total);
-}
-
-fn get_dir_list(root: &NodePointer) -> Vec<NodePointer> {
-  let mut children_folders: Vec<NodePointer> = root
-    .borrow()
-    .children
-    .iter()
-    .filter(|(_k, v)| v.borrow().node_type == NodeType::Folder)
-    .map(|(_k, v)| get_dir_list(v))
-    .flatten()
-    .collect();
-  children_folders.push(root.clone());
-  children_folders
+  sizes
 }
 
 fn read_data() -> Input {
@@ -126,60 +86,12 @@ fn read_data() -> Input {
 }
 
 fn solve(input: Input) -> usize {
-  let root: NodePointer = Rc::new(RefCell::new(Node::create_root()));
-  let mut current: NodePointer = root.clone();
-  for cmd_line in input {
-    let cmd = ConsoleLine::from_line(&cmd_line);
-    match cmd {
-      ConsoleLine::CD(target) => match target.as_str() {
-        "/" => {
-          current = root.clone();
-        }
-        ".." => {
-          let prev: NodePointer = current.borrow().parent.as_ref().unwrap().clone();
-          current = prev;
-        }
-        other => {
-          let next = current.borrow().children.get(other).unwrap().clone();
-          current = next
-        }
-      },
-      ConsoleLine::Directory(dir_name) => {
-        let n = Node {
-          parent: Some(current.clone()),
-          size: None,
-          name: dir_name.clone(),
-          node_type: NodeType::Folder,
-          children: Default::default(),
-        };
-        let n_ref = Rc::new(RefCell::new(n));
-
-        let mut cnode: RefMut<_> = current.borrow_mut();
-        cnode.children.insert(dir_name.clone(), n_ref);
-      }
-      ConsoleLine::File(file_name, size) => {
-        let n = Node {
-          parent: Some(current.clone()),
-          size: Some(size),
-          name: file_name.clone(),
-          node_type: NodeType::File,
-          children: Default::default(),
-        };
-        let n_ref = Rc::new(RefCell::new(n));
-
-        let mut cnode: RefMut<_> = current.borrow_mut();
-        cnode.children.insert(file_name.clone(), n_ref);
-      }
-      ConsoleLine::LS => {
-        // print_path(&current)
-      }
-    }
-  }
-  calculate_folder_size(&root);
-  let all = get_dir_list(&root);
-  all
-    .iter()
-    .map(|n| n.borrow().size.unwrap())
+  let tree = build_tree(&input);
+  let sizes = calculate_sizes(&tree);
+  tree
+    .ids()
+    .filter(|&id| *tree.get(id) == Entry::Folder)
+    .map(|id| sizes[id.index()])
     .filter(|&s| s <= LIMIT)
     .sum()
 }
@@ -194,9 +106,48 @@ pub fn seven() {
 
 #[cfg(test)]
 mod tests {
+  use super::{build_tree, calculate_sizes, solve};
+  use indoc::indoc;
 
   #[test]
   fn simple() {
     assert!(true);
   }
+
+  #[test]
+  fn sample() {
+    let input: Vec<String> = SAMPLE.lines().map(|l| l.to_owned()).collect();
+    let tree = build_tree(&input);
+    let sizes = calculate_sizes(&tree);
+    let e = tree.resolve(tree.root(), "a/e").unwrap();
+    assert_eq!(sizes[e.index()], 584);
+    assert_eq!(sizes[tree.root().index()], 48381165);
+    assert_eq!(solve(input), 95437);
+  }
+
+  const SAMPLE: &str = indoc! {"
+    $ cd /
+    $ ls
+    dir a
+    14848514 b.txt
+    8504156 c.dat
+    dir d
+    $ cd a
+    $ ls
+    dir e
+    29116 f
+    2557 g
+    62596 h.lst
+    $ cd e
+    $ ls
+    584 i
+    $ cd ..
+    $ cd ..
+    $ cd d
+    $ ls
+    4060174 j
+    8033020 d.log
+    5626152 d.ext
+    7214296 k
+  "};
 }
@@ -1,59 +1,163 @@
+use crate::render::{self, Anchor, Frame, Palette};
+use crate::simulation::Simulation;
 use std::fs::File;
 use std::io::{prelude::*, BufReader};
-use std::ops::{Add, Sub};
 
 type Input = Vec<String>;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
-struct Point {
-  x: isize,
-  y: isize,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GasDir {
+  Left,
+  Right,
 }
 
-impl Add for Point {
-  type Output = Point;
-
-  fn add(self, other: Point) -> Point {
-    Point {
-      x: self.x + other.x,
-      y: self.y + other.y,
+impl GasDir {
+  fn from_char(c: char) -> GasDir {
+    match c {
+      '<' => GasDir::Left,
+      '>' => GasDir::Right,
+      _ => unreachable!(),
     }
   }
 }
 
-impl Sub for Point {
-  type Output = Point;
+// Rows are bitmasks of the 7 columns, the leftmost column being the highest
+// bit. Rocks are listed bottom row first, already 2 units away from the left
+// wall.
+type Rock = &'static [u8];
+
+const ROCKS: [Rock; 5] = [
+  &[0b0011110],
+  &[0b0001000, 0b0011100, 0b0001000],
+  &[0b0011100, 0b0000100, 0b0000100],
+  &[0b0010000, 0b0010000, 0b0010000, 0b0010000],
+  &[0b0011000, 0b0011000],
+];
+
+const LEFT_WALL: u8 = 0b1000000;
+const RIGHT_WALL: u8 = 0b0000001;
+const FINGERPRINT_ROWS: usize = 32;
 
-  fn sub(self, other: Point) -> Point {
-    Point {
-      x: self.x - other.x,
-      y: self.y - other.y,
+#[derive(Debug, Clone)]
+struct Tower {
+  rows: Vec<u8>,
+  jets: Vec<GasDir>,
+  jet: usize,
+  rock: usize,
+}
+
+impl Tower {
+  fn new(jets: Vec<GasDir>) -> Self {
+    Self {
+      rows: vec![],
+      jets,
+      jet: 0,
+      rock: 0,
     }
   }
-}
 
-impl Point {
-  const fn new(x: isize, y: isize) -> Point {
-    Point { x, y }
+  fn height(&self) -> usize {
+    self.rows.len()
   }
-}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum GasDir {
-  Left,
-  Right,
+  fn collides(&self, rock: &[u8], y: usize) -> bool {
+    rock
+      .iter()
+      .enumerate()
+      .any(|(i, row)| self.rows.get(y + i).is_some_and(|r| r & row != 0))
+  }
+
+  fn push(&self, rock: &[u8], y: usize, dir: GasDir) -> Option<Vec<u8>> {
+    let pushed: Vec<u8> = match dir {
+      GasDir::Left if rock.iter().all(|r| r & LEFT_WALL == 0) => {
+        rock.iter().map(|r| r << 1).collect()
+      }
+      GasDir::Right if rock.iter().all(|r| r & RIGHT_WALL == 0) => {
+        rock.iter().map(|r| r >> 1).collect()
+      }
+      _ => return None,
+    };
+    if self.collides(&pushed, y) {
+      None
+    } else {
+      Some(pushed)
+    }
+  }
+
+  fn drop_rock(&mut self) {
+    let mut rock: Vec<u8> = ROCKS[self.rock].to_vec();
+    self.rock = (self.rock + 1) % ROCKS.len();
+    let mut y = self.height() + 3;
+    loop {
+      let dir = self.jets[self.jet];
+      self.jet = (self.jet + 1) % self.jets.len();
+      if let Some(pushed) = self.push(&rock, y, dir) {
+        rock = pushed;
+      }
+      if y == 0 || self.collides(&rock, y - 1) {
+        break;
+      }
+      y -= 1;
+    }
+    for (i, row) in rock.into_iter().enumerate() {
+      if y + i == self.rows.len() {
+        self.rows.push(0);
+      }
+      self.rows[y + i] |= row;
+    }
+  }
 }
 
-impl GasDir {
-  fn from_char(c: char) -> GasDir {
-    match c {
-      '<' => GasDir::Left,
-      '>' => GasDir::Right,
-      _ => unreachable!(),
+impl Simulation for Tower {
+  type Key = (usize, usize, Vec<u8>);
+
+  fn step(&mut self) {
+    self.drop_rock();
+  }
+
+  fn state_key(&self) -> Self::Key {
+    let top: Vec<u8> = self
+      .rows
+      .iter()
+      .rev()
+      .take(FINGERPRINT_ROWS)
+      .copied()
+      .collect();
+    (self.rock, self.jet, top)
+  }
+
+  fn render(&self) -> String {
+    let mut out = String::new();
+    for row in self.rows.iter().rev() {
+      out.push('|');
+      for column in (0..7).rev() {
+        out.push(if row & (1 << column) != 0 { '#' } else { '.' });
+      }
+      out.push_str("|\n");
     }
+    out.push_str("+-------+\n");
+    out
   }
 }
 
+fn parse_jets(input: &Input) -> Vec<GasDir> {
+  input
+    .first()
+    .unwrap()
+    .trim()
+    .chars()
+    .map(GasDir::from_char)
+    .collect()
+}
+
+const ROCK_COUNT: usize = 2022;
+const ROCK_COUNT_V2: usize = 1_000_000_000_000;
+
+fn solve(input: Input, rocks: usize) -> usize {
+  let tower = Tower::new(parse_jets(&input));
+  tower.extrapolate(|t| t.height() as isize, rocks) as usize
+}
+
 fn read_data() -> Input {
   let filename = format!("./resources/17.txt");
   let file: File = File::open(&filename).expect(&format!("Cannot open file {}", &filename));
@@ -62,28 +166,91 @@ fn read_data() -> Input {
   line_iter.map(|l| l.unwrap()).collect()
 }
 
-fn solve(input: Input) -> usize {
-  let pattern: Vec<GasDir> = input
-    .first()
-    .unwrap()
-    .chars()
-    .map(GasDir::from_char)
+const ANIMATED_ROCKS: usize = 200;
+
+pub fn render() {
+  let input = read_data();
+  let mut tower = Tower::new(parse_jets(&input));
+  let palette = Palette::new(&[
+    ('.', render::BLACK),
+    ('#', [160, 110, 60]),
+    ('|', [90, 90, 90]),
+    ('-', [90, 90, 90]),
+    ('+', [90, 90, 90]),
+  ]);
+  let frames: Vec<Frame> = render::record(&mut tower, 1, ANIMATED_ROCKS, &palette)
+    .iter()
+    .map(|f| f.scaled(4))
     .collect();
-  let mut pattern_iter = pattern.iter().cycle();
-  unimplemented!()
+  render::save_gif(
+    "./output/17/tower.gif",
+    &frames,
+    &palette,
+    5,
+    Anchor::BottomLeft,
+  )
+  .unwrap();
+  render::save_numbered("./output/17/frames", &frames, &palette).unwrap();
+  tower.run_for(ROCK_COUNT - ANIMATED_ROCKS);
+  Frame::from_text(&tower.render(), &palette)
+    .save_pgm("./output/17/tower.pgm", &palette)
+    .unwrap();
 }
 
 pub fn seventeen() {
   let input = read_data();
-  let score = solve(input);
+  let score = solve(input, ROCK_COUNT_V2);
   println!("{score}")
 }
 
 #[cfg(test)]
 mod tests {
+  use super::{parse_jets, solve, Tower, ROCK_COUNT, ROCK_COUNT_V2};
+  use crate::cycle;
+  use crate::simulation::Simulation;
+  use indoc::indoc;
+
+  const SAMPLE: &str = ">>><<><>><<<>><>>><<<>>><<<><<<>><>><<>>";
+
+  fn input() -> Vec<String> {
+    vec![SAMPLE.to_string()]
+  }
 
   #[test]
   fn simple() {
-    assert!(true);
+    let mut tower = Tower::new(parse_jets(&input()));
+    tower.run_for(ROCK_COUNT);
+    assert_eq!(tower.height(), 3068);
+    assert_eq!(solve(input(), ROCK_COUNT), 3068);
+  }
+
+  #[test]
+  fn render() {
+    let mut tower = Tower::new(parse_jets(&input()));
+    tower.run_for(3);
+    let expected = indoc! {"
+      |..#....|
+      |..#....|
+      |####...|
+      |..###..|
+      |...#...|
+      |..####.|
+      +-------+
+    "};
+    assert_eq!(tower.render(), expected);
+  }
+
+  #[test]
+  fn v2() {
+    assert_eq!(solve(input(), ROCK_COUNT_V2), 1514285714288);
+  }
+
+  #[test]
+  fn cycle_detection() {
+    let tower = Tower::new(parse_jets(&input()));
+    let hashed = tower.clone().find_cycle();
+    let brent = cycle::brent(&tower, Tower::step, Tower::state_key);
+    assert_eq!(hashed.length, brent.length);
+    assert_eq!(hashed.length % 5, 0);
   }
 }
@@ -0,0 +1,163 @@
+use crate::cycle::{self, Cycle};
+use std::hash::Hash;
+
+/// Puzzle whose state advances one tick at a time.
+pub trait Simulation {
+  type Key: Eq + Hash;
+
+  /// Advances one tick. Does nothing once the simulation is finished.
+  fn step(&mut self);
+
+  fn is_finished(&self) -> bool {
+    false
+  }
+
+  /// Identifies the state well enough to tell when it repeats.
+  fn state_key(&self) -> Self::Key;
+
+  /// Human readable picture of the current state.
+  fn render(&self) -> String;
+
+  /// Steps until finished. Returns the number of steps taken.
+  fn run(&mut self) -> usize {
+    self.run_until(|_| false)
+  }
+
+  /// Steps until `done` holds or the simulation is finished. Returns the
+  /// number of steps taken.
+  fn run_until(&mut self, mut done: impl FnMut(&Self) -> bool) -> usize {
+    let mut steps = 0;
+    while !self.is_finished() && !done(self) {
+      self.step();
+      steps += 1;
+    }
+    steps
+  }
+
+  /// Steps `n` times, or fewer if the simulation finishes first. Returns the
+  /// number of steps taken.
+  fn run_for(&mut self, n: usize) -> usize {
+    let mut steps = 0;
+    self.run_until(|_| {
+      steps += 1;
+      steps > n
+    })
+  }
+
+  /// Steps until a state repeats; see `cycle::find_cycle`.
+  fn find_cycle(&mut self) -> Cycle
+  where
+    Self: Sized,
+  {
+    cycle::find_cycle(self, Self::step, Self::state_key)
+  }
+
+  /// Value of `counter` after `target` steps, simulating only until the first
+  /// repeated state.
+  fn extrapolate(self, counter: impl FnMut(&Self) -> isize, target: usize) -> isize
+  where
+    Self: Sized,
+  {
+    cycle::extrapolate(self, Self::step, Self::state_key, counter, target)
+  }
+
+  /// Rendering of every state, from the current one until the simulation is
+  /// finished.
+  fn frames(&mut self) -> Frames<'_, Self>
+  where
+    Self: Sized,
+  {
+    Frames {
+      simulation: self,
+      done: false,
+    }
+  }
+}
+
+pub struct Frames<'a, S: Simulation> {
+  simulation: &'a mut S,
+  done: bool,
+}
+
+impl<S: Simulation> Iterator for Frames<'_, S> {
+  type Item = String;
+
+  fn next(&mut self) -> Option<String> {
+    if self.done {
+      return None;
+    }
+    let frame = self.simulation.render();
+    if self.simulation.is_finished() {
+      self.done = true;
+    } else {
+      self.simulation.step();
+    }
+    Some(frame)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Simulation;
+
+  // Counts up to a limit, wrapping around every `period` steps.
+  struct Counter {
+    value: usize,
+    steps: usize,
+    period: usize,
+    limit: usize,
+  }
+
+  impl Simulation for Counter {
+    type Key = usize;
+
+    fn step(&mut self) {
+      if !self.is_finished() {
+        self.steps += 1;
+        self.value = (self.value + 1) % self.period;
+      }
+    }
+
+    fn is_finished(&self) -> bool {
+      self.steps == self.limit
+    }
+
+    fn state_key(&self) -> usize {
+      self.value
+    }
+
+    fn render(&self) -> String {
+      self.value.to_string()
+    }
+  }
+
+  fn counter(limit: usize) -> Counter {
+    Counter {
+      value: 0,
+      steps: 0,
+      period: 3,
+      limit,
+    }
+  }
+
+  #[test]
+  fn running() {
+    let mut c = counter(10);
+    assert_eq!(c.run_for(4), 4);
+    assert_eq!(c.value, 1);
+    assert_eq!(c.run_until(|c| c.value == 0), 2);
+    assert_eq!(c.run(), 4);
+    assert!(c.is_finished());
+    assert_eq!(c.run_for(4), 0);
+  }
+
+  #[test]
+  fn cycles_and_frames() {
+    let cycle = counter(100).find_cycle();
+    assert_eq!((cycle.start, cycle.length), (0, 3));
+    let steps = counter(1000).extrapolate(|c| c.steps as isize, 500);
+    assert_eq!(steps, 500);
+    let frames: Vec<String> = counter(4).frames().collect();
+    assert_eq!(frames, vec!["0", "1", "2", "0", "1"]);
+  }
+}
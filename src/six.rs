@@ -1,7 +1,6 @@
-use std::collections::HashSet;
+use crate::bitset::BitSet64;
 use std::fs::File;
 use std::io::{prelude::*, BufReader};
-use std::iter::FromIterator;
 
 fn read_data() -> String {
   let filename = format!("./resources/6.txt");
@@ -27,7 +26,7 @@ fn detect_marker(input: &str, chunk_size: usize) -> usize {
   while found.is_none() {
     let end = start + chunk_size - 1;
     let chunk = &input[start..=end];
-    let set: HashSet<char> = HashSet::from_iter(chunk.chars());
+    let set = BitSet64::from_letters(chunk);
     if set.len() == chunk_size {
       found = Some(end);
     } else {
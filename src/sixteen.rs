@@ -1,8 +1,9 @@
+use crate::bitset::BitSet64;
+use crate::extract::{integers, words};
+use crate::graph::{Graph, NodeId};
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::cell::RefCell;
-use std::collections::HashMap;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{prelude::*, BufReader};
 
 type Input = Vec<String>;
@@ -17,9 +18,35 @@ struct Node {
 
 impl Node {
   fn from_line(line: &str) -> Node {
+    Self::from_line_fast(line).unwrap_or_else(|| Self::from_line_regex(line))
+  }
+
+  fn from_line_fast(line: &str) -> Option<Node> {
+    let mut iter = words(line);
+    if iter.next()? != "Valve" {
+      return None;
+    }
+    let name = iter.next()?.to_string();
+    let rate = integers(line).next()?.try_into().ok()?;
+    let children: Vec<String> = iter
+      .filter(|w| w.bytes().all(|b| b.is_ascii_uppercase()))
+      .map(|w| w.to_string())
+      .collect();
+    if children.is_empty() {
+      return None;
+    }
+    Some(Node {
+      name,
+      rate,
+      is_open: false,
+      children,
+    })
+  }
+
+  fn from_line_regex(line: &str) -> Node {
     lazy_static! {
       static ref RE: Regex =
-        Regex::new(r"^Valve (\w+) has flow rate=(\d+); tunnels lead to valves (.*)$").unwrap();
+        Regex::new(r"^Valve (\w+) has flow rate=(\d+); tunnels? leads? to valves? (.*)$").unwrap();
     }
     let caps = RE.captures(line).unwrap();
     let name = caps.get(1).unwrap().as_str().to_string();
@@ -48,26 +75,61 @@ fn read_data() -> Input {
   line_iter.map(|l| l.unwrap()).collect()
 }
 
+type Valves = Graph<usize>;
+
+fn build_graph(nodes: &[Node]) -> Valves {
+  let mut graph: Valves = Graph::new();
+  for node in nodes {
+    let id = graph.add_node(&node.name, node.rate);
+    for child in node.children.iter() {
+      let child_id = graph.intern(child);
+      graph.add_edge(id, child_id, 1);
+    }
+  }
+  graph
+}
+
+// Keeps the starting valve plus the ones worth opening, the start being
+// node 0 of the result.
+fn interesting_valves(graph: &Valves) -> Valves {
+  let start = graph.id(START).unwrap();
+  let mut keep: Vec<NodeId> = vec![start];
+  keep.extend(graph.ids().filter(|&id| id != start && *graph.data(id) > 0));
+  graph.compress(&keep)
+}
+
+fn best_pressure(valves: &Valves, current: NodeId, time_left: usize, opened: BitSet64) -> usize {
+  valves
+    .neighbours(current)
+    .iter()
+    .filter(|(next, distance)| !opened.contains(*next) && distance + 1 < time_left)
+    .map(|&(next, distance)| {
+      let remaining = time_left - distance - 1;
+      let released = valves.data(next) * remaining;
+      released + best_pressure(valves, next, remaining, opened.with(next))
+    })
+    .max()
+    .unwrap_or(0)
+}
+
+const START: &str = "AA";
 const MINUTES: usize = 30;
+
 fn solve(input: Input) -> usize {
-  let node_list: Vec<Node> = input.iter().map(|l| Node::from_line(l)).collect();
-  let mut nodes: HashMap<String, RefCell<Node>> = HashMap::new();
-  for node in node_list {
-    nodes.insert(node.name.clone(), RefCell::new(node));
-  }
-  let current = "AA".to_string();
-  let mut score: usize = 0;
-  for i in 1..=MINUTES {
-    let pending = nodes
-      .iter()
-      .filter(|(_, nref)| {
-        let n = nref.borrow();
-        !n.is_open && n.rate > 0
-      })
-      .count();
-  }
+  let nodes: Vec<Node> = input.iter().map(|l| Node::from_line(l)).collect();
+  let valves = interesting_valves(&build_graph(&nodes));
+  best_pressure(&valves, 0, MINUTES, BitSet64::new().with(0))
+}
 
-  1
+pub fn render() {
+  let input = read_data();
+  let nodes: Vec<Node> = input.iter().map(|l| Node::from_line(l)).collect();
+  let graph = build_graph(&nodes);
+  let valves = interesting_valves(&graph);
+  let label = |id: NodeId, rate: &usize| format!("{} ({rate})", valves.name(id));
+  fs::create_dir_all("./output/16").unwrap();
+  fs::write("./output/16/tunnels.dot", graph.to_dot()).unwrap();
+  fs::write("./output/16/valves.dot", valves.to_dot_with(label)).unwrap();
 }
 
 pub fn sixteen() {
@@ -79,6 +141,74 @@ pub fn sixteen() {
 #[cfg(test)]
 mod tests {
   use super::*;
+  use std::time::Instant;
+
+  const SAMPLE: &str = indoc::indoc! {"
+    Valve AA has flow rate=0; tunnels lead to valves DD, II, BB
+    Valve BB has flow rate=13; tunnels lead to valves CC, AA
+    Valve CC has flow rate=2; tunnels lead to valves DD, BB
+    Valve DD has flow rate=20; tunnels lead to valves CC, AA, EE
+    Valve EE has flow rate=3; tunnels lead to valves FF, DD
+    Valve FF has flow rate=0; tunnels lead to valves EE, GG
+    Valve GG has flow rate=0; tunnels lead to valves FF, HH
+    Valve HH has flow rate=22; tunnel leads to valve GG
+    Valve II has flow rate=0; tunnels lead to valves AA, JJ
+    Valve JJ has flow rate=21; tunnel leads to valve II
+  "};
+
+  #[test]
+  fn parse_fast_path() {
+    for line in SAMPLE.lines() {
+      let fast = Node::from_line_fast(line).unwrap();
+      let regex = Node::from_line_regex(line);
+      assert_eq!(fast.name, regex.name);
+      assert_eq!(fast.rate, regex.rate);
+      assert_eq!(fast.children, regex.children);
+    }
+    assert!(Node::from_line_fast("Valve AA has flow rate=0").is_none());
+  }
+
+  #[test]
+  fn graph() {
+    let nodes: Vec<Node> = SAMPLE.lines().map(Node::from_line).collect();
+    let graph = build_graph(&nodes);
+    assert_eq!(graph.len(), 10);
+    let jj = graph.id("JJ").unwrap();
+    assert_eq!(graph.neighbours(jj), &[(graph.id("II").unwrap(), 1)]);
+
+    let valves = interesting_valves(&graph);
+    assert_eq!(valves.len(), 7);
+    assert_eq!(valves.name(0), "AA");
+    let hh = valves.id("HH").unwrap();
+    let distance = valves.neighbours(0).iter().find(|(id, _)| *id == hh);
+    assert_eq!(distance, Some(&(hh, 5)));
+  }
+
+  #[test]
+  fn simple() {
+    let input: Input = SAMPLE.lines().map(|l| l.to_string()).collect();
+    assert_eq!(solve(input), 1651);
+  }
+
+  // cargo test --release -- --ignored --nocapture bench
+  #[test]
+  #[ignore]
+  fn bench_parse() {
+    let lines: Vec<&str> = SAMPLE.lines().collect();
+    let rounds = 20000;
+    let time = |f: &dyn Fn(&str) -> Node| {
+      let start = Instant::now();
+      let mut total = 0;
+      for _ in 0..rounds {
+        total += lines.iter().map(|l| f(l).rate).sum::<usize>();
+      }
+      (start.elapsed(), total)
+    };
+    let (regex, a) = time(&Node::from_line_regex);
+    let (fast, b) = time(&|l| Node::from_line_fast(l).unwrap());
+    assert_eq!(a, b);
+    println!("valves: regex {regex:?}, fast {fast:?}");
+  }
 
   #[test]
   fn parse() {
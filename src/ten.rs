@@ -1,3 +1,5 @@
+use crate::ocr;
+use crate::simulation::Simulation;
 use std::fs::File;
 use std::io::{prelude::*, BufReader};
 
@@ -53,71 +55,98 @@ fn sprite_overlap(x: isize, pos: usize) -> bool {
 
 const SPRITE_W: usize = 40;
 
-fn solve(input: Input) -> isize {
-  let mut cycle: isize = 1;
-  let mut pending_cycles: usize = 0;
-  let mut poi_list: Vec<isize> = vec![];
-  let mut x: isize = 1;
-  let mut current_op: Option<&Operation> = Some(&Operation::Noop);
-  let mut sprite_counter: usize = 0;
-  let mut sprite: Vec<String> = vec![];
-  let mut sprite_line: Vec<char> = vec![];
-  let operations: Vec<Operation> = input.iter().map(Operation::from_line).collect();
-  let mut operations_iter = operations.iter();
-  loop {
-    if pending_cycles == 0 {
-      //apply op
-      let op = current_op.unwrap();
-      if let Operation::Add(i) = op {
-        x += i;
-      }
-
-      //charge next
-      current_op = operations_iter.next();
-    }
+// One step is one clock cycle.
+struct Cpu {
+  program: Vec<Operation>,
+  pc: usize,
+  // cycles already spent on `program[pc]`
+  progress: usize,
+  cycle: isize,
+  x: isize,
+  signal: isize,
+  screen: Vec<char>,
+}
 
-    if sprite_overlap(x, sprite_counter) {
-      sprite_line.push('#')
-    } else {
-      sprite_line.push('.')
+impl Cpu {
+  fn new(input: &Input) -> Self {
+    Self {
+      program: input.iter().map(Operation::from_line).collect(),
+      pc: 0,
+      progress: 0,
+      cycle: 1,
+      x: 1,
+      signal: 0,
+      screen: vec![],
     }
-    sprite_counter += 1;
+  }
+}
 
-    if sprite_counter == SPRITE_W {
-      let line: String = sprite_line.iter().collect();
-      sprite_counter = 0;
-      sprite_line = vec![];
-      sprite.push(line);
-    }
+impl Simulation for Cpu {
+  type Key = (usize, usize, isize);
 
-    //compute points of interest
-    if is_point_of_interest(cycle) {
-      poi_list.push(cycle * x);
+  fn step(&mut self) {
+    let Some(op) = self.program.get(self.pc) else {
+      return;
+    };
+    // during the cycle
+    let pixel = if sprite_overlap(self.x, self.screen.len() % SPRITE_W) {
+      '#'
+    } else {
+      '.'
+    };
+    self.screen.push(pixel);
+    if is_point_of_interest(self.cycle) {
+      self.signal += self.cycle * self.x;
     }
 
-    if pending_cycles == 0 {
-      if current_op.is_some() {
-        pending_cycles = current_op.unwrap().cycles();
-      } else {
-        break;
+    // after the cycle
+    self.progress += 1;
+    if self.progress == op.cycles() {
+      if let Operation::Add(i) = op {
+        self.x += i;
       }
+      self.pc += 1;
+      self.progress = 0;
     }
+    self.cycle += 1;
+  }
+
+  fn is_finished(&self) -> bool {
+    self.pc == self.program.len()
+  }
 
-    pending_cycles -= 1;
-    cycle += 1;
+  fn state_key(&self) -> Self::Key {
+    (self.pc, self.progress, self.x)
   }
 
-  for line in sprite {
-    println!("{line}");
+  fn render(&self) -> String {
+    self
+      .screen
+      .chunks(SPRITE_W)
+      .map(|line| line.iter().collect::<String>() + "\n")
+      .collect()
   }
+}
 
-  poi_list.iter().sum()
+fn solve(input: Input) -> isize {
+  let mut cpu = Cpu::new(&input);
+  cpu.run();
+  cpu.signal
+}
+
+fn solve_v2(input: Input) -> String {
+  let mut cpu = Cpu::new(&input);
+  cpu.run();
+  let screen = cpu.render();
+  ocr::recognize(&screen).unwrap_or_else(|e| panic!("{e}\n{screen}"))
 }
 
 pub fn ten() {
   let input = read_data();
-  let score = solve(input);
-  println!("{score}")
+  let score = solve(input.clone());
+  println!("{score}");
+  let letters = solve_v2(input);
+  println!("{letters}")
 }
 
 #[cfg(test)]
@@ -125,6 +154,9 @@ mod tests {
 
   use super::is_point_of_interest;
   use super::solve;
+  use super::Cpu;
+  use crate::simulation::Simulation;
+  use indoc::indoc;
   #[test]
   fn simple() {
     let input: Vec<String> = SAMPLE.lines().map(|l| l.to_owned()).collect();
@@ -132,6 +164,22 @@ mod tests {
     assert_eq!(score, 13140);
   }
 
+  #[test]
+  fn screen() {
+    let input: Vec<String> = SAMPLE.lines().map(|l| l.to_owned()).collect();
+    let mut cpu = Cpu::new(&input);
+    assert_eq!(cpu.run(), 240);
+    let expected = indoc! {"
+      ##..##..##..##..##..##..##..##..##..##..
+      ###...###...###...###...###...###...###.
+      ####....####....####....####....####....
+      #####.....#####.....#####.....#####.....
+      ######......######......######......####
+      #######.......#######.......#######.....
+    "};
+    assert_eq!(cpu.render(), expected);
+  }
+
   #[test]
   fn poi() {
     assert!(!is_point_of_interest(19));
@@ -1,3 +1,5 @@
+use crate::parser::{parse_line, ParseResult, Parser};
+use crate::records;
 use std::fs::File;
 use std::io::{prelude::*, BufReader};
 
@@ -10,45 +12,20 @@ enum PacketElement {
 }
 
 impl PacketElement {
-  fn from_str(s: &str) -> Self {
-    if s.starts_with('[') {
-      let mut list = Vec::new();
-      let mut current = String::new();
-      let mut depth = 0;
-      for c in s.chars() {
-        match c {
-          '[' => {
-            depth += 1;
-            if depth > 1 {
-              current.push(c);
-            }
-          }
-          ']' => {
-            depth -= 1;
-            if depth == 0 {
-              list.push(PacketElement::from_str(&current));
-              current = String::new();
-            } else {
-              current.push(c);
-            }
-          }
-          ',' => {
-            if depth == 1 {
-              list.push(PacketElement::from_str(&current));
-              current = String::new();
-            } else {
-              current.push(c);
-            }
-          }
-          _ => current.push(c),
-        }
-      }
-      PacketElement::List(list)
-    } else if s.is_empty() {
-      PacketElement::List(Vec::new())
-    } else {
-      PacketElement::Integer(s.parse().unwrap())
+  fn parse(p: &mut Parser) -> ParseResult<Self> {
+    if !p.try_literal("[") {
+      return Ok(PacketElement::Integer(p.signed()?));
+    }
+    if p.try_literal("]") {
+      return Ok(PacketElement::List(Vec::new()));
     }
+    let list = p.separated(",", PacketElement::parse)?;
+    p.literal("]")?;
+    Ok(PacketElement::List(list))
+  }
+
+  fn from_str(s: &str) -> Self {
+    parse_line(s, PacketElement::parse).unwrap()
   }
 }
 
@@ -105,12 +82,16 @@ fn read_data() -> Input {
 }
 
 fn solve(input: Input) -> usize {
-  let mapped: Vec<PacketElement> = input
-    .into_iter()
-    .filter(|p| !p.is_empty())
-    .map(|s| PacketElement::from_str(s.as_str()))
+  let pairs: Vec<Vec<PacketElement>> = records::chunks(&input, 2)
+    .map(|record| {
+      let record = record.unwrap_or_else(|e| panic!("{e}"));
+      record
+        .lines
+        .iter()
+        .map(|s| PacketElement::from_str(s.as_str()))
+        .collect()
+    })
     .collect();
-  let pairs: Vec<&[PacketElement]> = mapped.chunks_exact(2).collect();
   let mut score = 0;
   for (i, p) in pairs.iter().enumerate() {
     if p[0] < p[1] {
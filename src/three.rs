@@ -1,4 +1,8 @@
+use crate::bitset::{index_letter, letter_index, BitSet64, GrowableBitSet};
+use crate::cli::{Options, Part};
+use crate::records::{self, RecordError};
 use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
 use std::fs::File;
 use std::io::{prelude::*, BufReader};
 
@@ -10,66 +14,263 @@ fn read_data() -> Vec<String> {
   line_iter.map(|l| l.unwrap()).collect()
 }
 
-const A_UPPER: usize = 'A' as usize;
-const A_LOWER: usize = 'a' as usize;
-
 fn char_to_priority(c: char) -> usize {
-  let int = c as usize;
-  if int >= A_LOWER {
-    return int - A_LOWER + 1;
-  } else {
-    return int - A_UPPER + 27;
+  letter_index(c).unwrap()
+}
+
+const GROUP_SIZE: usize = 3;
+
+// Priority of the only item in `common`, `line` being where its lines start.
+fn single_item(common: BitSet64, line: usize) -> Result<usize, RecordError> {
+  let message = match common.len() {
+    1 => return Ok(common.first().unwrap()),
+    0 => "no item in common".to_string(),
+    _ => format!(
+      "several items in common: {}",
+      common.letters().collect::<String>()
+    ),
+  };
+  Err(RecordError { line, message })
+}
+
+// Items of `s`, which must only hold letters; `line` is where it comes from.
+fn rucksack_items(s: &str, line: usize) -> Result<BitSet64, RecordError> {
+  match s.chars().find(|&c| letter_index(c).is_none()) {
+    Some(c) => Err(RecordError {
+      line,
+      message: format!("invalid character `{c}`"),
+    }),
+    None => Ok(BitSet64::from_letters(s)),
   }
 }
 
-fn find_item(line: &String) -> char {
+fn find_item(line: &str, number: usize) -> Result<usize, RecordError> {
+  // both halves are split on a byte index
+  if !line.is_ascii() || line.len() % 2 == 1 {
+    return Err(RecordError {
+      line: number,
+      message: "expected an even number of ASCII characters".to_string(),
+    });
+  }
   let n = line.len();
   let (a, b) = line.split_at(n / 2);
-  let char_set: HashSet<char> = a.chars().collect();
-  for c in b.chars() {
-    if char_set.contains(&c) {
-      return c;
+  single_item(
+    rucksack_items(a, number)? & rucksack_items(b, number)?,
+    number,
+  )
+}
+
+fn solve(input: &[String]) -> Result<usize, RecordError> {
+  input
+    .iter()
+    .enumerate()
+    .map(|(i, l)| find_item(l, i + 1))
+    .sum()
+}
+
+fn solve_v2(input: &[String], group: usize) -> Result<usize, RecordError> {
+  records::chunks(input, group)
+    .map(|record| {
+      let record = record?;
+      let mut common = rucksack_items(&record.lines[0], record.line)?;
+      for (i, line) in record.lines.iter().enumerate().skip(1) {
+        common = common & rucksack_items(line, record.line + i)?;
+      }
+      single_item(common, record.line)
+    })
+    .sum()
+}
+
+/// Three rucksacks, by 0-based position in the input, and the only item they
+/// all hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Group {
+  members: [usize; 3],
+  badge: usize,
+}
+
+impl Display for Group {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    let [a, b, c] = self.members.map(|m| m + 1);
+    let badge = index_letter(self.badge).unwrap();
+    write!(f, "lines {a}, {b}, {c}: badge {badge} ({})", self.badge)
+  }
+}
+
+// Backtracking over the groups the first ungrouped rucksack can join,
+// remembering which sets of grouped rucksacks led nowhere.
+struct BadgeSearch<'a> {
+  items: &'a [BitSet64],
+  grouped: GrowableBitSet,
+  groups: Vec<Group>,
+  dead_ends: HashSet<GrowableBitSet>,
+}
+
+impl BadgeSearch<'_> {
+  fn search(&mut self) -> bool {
+    let n = self.items.len();
+    let Some(first) = (0..n).find(|&i| !self.grouped.contains(i)) else {
+      return true;
+    };
+    if self.dead_ends.contains(&self.grouped) {
+      return false;
     }
+    for second in first + 1..n {
+      let shared = self.items[first] & self.items[second];
+      if self.grouped.contains(second) || shared.is_empty() {
+        continue;
+      }
+      for third in second + 1..n {
+        let badge = shared & self.items[third];
+        if self.grouped.contains(third) || badge.len() != 1 {
+          continue;
+        }
+        let members = [first, second, third];
+        members.iter().for_each(|&m| {
+          self.grouped.insert(m);
+        });
+        self.groups.push(Group {
+          members,
+          badge: badge.first().unwrap(),
+        });
+        if self.search() {
+          return true;
+        }
+        self.groups.pop();
+        members.iter().for_each(|&m| {
+          self.grouped.remove(m);
+        });
+      }
+    }
+    self.dead_ends.insert(self.grouped.clone());
+    false
   }
-  unreachable!()
 }
 
-fn part_one() {
-  let input = read_data();
-  let score: usize = input.iter().map(find_item).map(char_to_priority).sum();
-  println!("{score}");
+/// Splits the rucksacks, whatever their order, into groups of three sharing
+/// exactly one item. `None` when no such split exists, an error when a line
+/// holds anything but letters.
+fn partition(input: &[String]) -> Result<Option<Vec<Group>>, RecordError> {
+  let items: Vec<BitSet64> = input
+    .iter()
+    .enumerate()
+    .map(|(i, line)| rucksack_items(line, i + 1))
+    .collect::<Result<_, _>>()?;
+  if !input.len().is_multiple_of(3) {
+    return Ok(None);
+  }
+  let mut search = BadgeSearch {
+    items: &items,
+    grouped: GrowableBitSet::new(),
+    groups: vec![],
+    dead_ends: HashSet::new(),
+  };
+  Ok(search.search().then_some(search.groups))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Problem {
+  OddLength(usize),
+  InvalidCharacters(String),
+  NoneShared,
+  SeveralShared(BitSet64),
+}
+
+impl Display for Problem {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Problem::OddLength(n) => write!(f, "odd length {n}"),
+      Problem::InvalidCharacters(chars) => write!(f, "invalid characters `{chars}`"),
+      Problem::NoneShared => write!(f, "no item shared by the compartments"),
+      Problem::SeveralShared(items) => {
+        let items: Vec<String> = items
+          .letters()
+          .map(|c| format!("{c} ({})", char_to_priority(c)))
+          .collect();
+        write!(f, "several items shared: {}", items.join(", "))
+      }
+    }
+  }
+}
+
+fn problems(line: &str) -> Vec<Problem> {
+  let mut found = vec![];
+  let n = line.chars().count();
+  if n % 2 == 1 {
+    found.push(Problem::OddLength(n));
+  }
+  let invalid: String = line
+    .chars()
+    .filter(|&c| letter_index(c).is_none())
+    .collect();
+  if !invalid.is_empty() {
+    found.push(Problem::InvalidCharacters(invalid));
+  }
+  let chars: Vec<char> = line.chars().collect();
+  let (a, b) = chars.split_at(n / 2);
+  let letters = |half: &[char]| BitSet64::from_letters(&half.iter().collect::<String>());
+  let common = letters(a) & letters(b);
+  match common.len() {
+    0 => found.push(Problem::NoneShared),
+    1 => {}
+    _ => found.push(Problem::SeveralShared(common)),
+  }
+  found
 }
 
-pub fn three() {
+/// Rucksacks that `solve` would reject or misread, with their line numbers.
+fn audit(input: &[String]) -> Vec<(usize, Vec<Problem>)> {
+  input
+    .iter()
+    .enumerate()
+    .map(|(i, line)| (i + 1, problems(line)))
+    .filter(|(_, found)| !found.is_empty())
+    .collect()
+}
+
+pub fn three(options: &Options) {
   let input = read_data();
-  let chunks = input.chunks_exact(3);
-  let score: usize = chunks
-    .map(|c| {
-      let union_set: Option<HashSet<_>> = c
-        .iter()
-        .map(|line| {
-          let set: HashSet<char> = line.chars().collect();
-          set
-        })
-        .fold(None, |acc, set| {
-          if acc.is_none() {
-            return Some(set.clone());
-          }
-          let prev = acc.unwrap();
-          let intersection: HashSet<char> = prev.intersection(&set).map(|r| r.to_owned()).collect();
-          return Some(intersection);
-        });
-      let as_vec: Vec<_> = union_set.unwrap().into_iter().collect();
-      let common_char = as_vec.first().unwrap().to_owned();
-      char_to_priority(common_char)
-    })
-    .sum();
-  println!("{score}");
+  if options.flag("audit") {
+    let report = audit(&input);
+    for (line, found) in &report {
+      let found: Vec<String> = found.iter().map(|p| p.to_string()).collect();
+      println!("line {line}: {}", found.join("; "));
+    }
+    println!(
+      "{} of {} rucksacks have problems",
+      report.len(),
+      input.len()
+    );
+    return;
+  }
+  if options.flag("unordered") {
+    match partition(&input).unwrap_or_else(|e| panic!("{e}")) {
+      Some(groups) => {
+        groups.iter().for_each(|g| println!("{g}"));
+        let sum: usize = groups.iter().map(|g| g.badge).sum();
+        println!("sum: {sum}");
+      }
+      None => println!("no split into groups of three sharing exactly one item"),
+    }
+    return;
+  }
+  let group = options.parsed("group").unwrap_or(GROUP_SIZE);
+  assert!(group > 0, "--group must be at least 1");
+  let score = match options.part() {
+    Part::One => solve(&input),
+    Part::Two => solve_v2(&input, group),
+  };
+  println!("{}", score.unwrap_or_else(|e| panic!("{e}")));
 }
 
 #[cfg(test)]
 mod tests {
-  use super::char_to_priority;
+  use super::{audit, char_to_priority, partition, solve, solve_v2, GROUP_SIZE};
+  use indoc::indoc;
+
+  fn sample() -> Vec<String> {
+    SAMPLE.lines().map(|l| l.to_owned()).collect()
+  }
 
   #[test]
   fn priority() {
@@ -78,4 +279,121 @@ mod tests {
     assert_eq!(27, char_to_priority('A'));
     assert_eq!(52, char_to_priority('Z'));
   }
+
+  #[test]
+  fn simple() {
+    assert_eq!(solve(&sample()), Ok(157));
+  }
+
+  #[test]
+  fn v2() {
+    assert_eq!(solve_v2(&sample(), GROUP_SIZE), Ok(70));
+  }
+
+  #[test]
+  fn ambiguous_groups() {
+    let input = sample();
+    let err = solve_v2(&input, 1).unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "line 1: several items in common: cfghprstvwFJMW"
+    );
+    let err = solve_v2(&input, 2).unwrap_err();
+    assert_eq!(err.to_string(), "line 1: several items in common: frsFM");
+    let err = solve_v2(&input, 6).unwrap_err();
+    assert_eq!(err.to_string(), "line 1: no item in common");
+    let err = solve(&["abcd".to_string()]).unwrap_err();
+    assert_eq!(err.to_string(), "line 1: no item in common");
+    for line in ["abc", "aéaé"] {
+      let err = solve(&["abca".to_string(), line.to_string()]).unwrap_err();
+      assert_eq!(
+        err.to_string(),
+        "line 2: expected an even number of ASCII characters"
+      );
+    }
+    // other characters are reported rather than skipped
+    let input = lines(&["ab", "a-", "ac"]);
+    let err = solve_v2(&input, GROUP_SIZE).unwrap_err();
+    assert_eq!(err.to_string(), "line 2: invalid character `-`");
+    let err = solve(&lines(&["ab-b"])).unwrap_err();
+    assert_eq!(err.to_string(), "line 1: invalid character `-`");
+  }
+
+  fn lines(input: &[&str]) -> Vec<String> {
+    input.iter().map(|l| l.to_string()).collect()
+  }
+
+  #[test]
+  fn unordered_groups() {
+    // not the puzzle's groups, but just as valid
+    let mut input = sample();
+    input.swap(1, 4);
+    let groups = partition(&input).unwrap().unwrap();
+    let found: Vec<String> = groups.iter().map(|g| g.to_string()).collect();
+    assert_eq!(
+      found,
+      vec!["lines 1, 2, 6: badge J (36)", "lines 3, 4, 5: badge q (17)"]
+    );
+
+    // grouping the first three greedily strands the rest
+    let input = lines(&["bc", "ab", "ab", "bd", "ad", "ab"]);
+    let groups = partition(&input).unwrap().unwrap();
+    let members: Vec<[usize; 3]> = groups.iter().map(|g| g.members).collect();
+    assert_eq!(members, vec![[0, 1, 3], [2, 4, 5]]);
+
+    assert_eq!(
+      partition(&lines(&["ab", "ab", "ab", "cd", "ce", "cf"])),
+      Ok(None)
+    );
+    assert_eq!(partition(&lines(&["a", "a"])), Ok(None));
+  }
+
+  #[test]
+  fn unordered_invalid_input() {
+    let err = partition(&lines(&["ab", "a-b", "ab"])).unwrap_err();
+    assert_eq!(err.to_string(), "line 2: invalid character `-`");
+  }
+
+  #[test]
+  fn audit_report() {
+    assert!(audit(&sample()).is_empty());
+    let input: Vec<String> = [
+      "abcaX",
+      "ab-1ab",
+      "abcd",
+      "aBcaBd",
+      "vJrwpWtwJgWrhcsFMMfFFhFp",
+    ]
+    .iter()
+    .map(|l| l.to_string())
+    .collect();
+    let report: Vec<(usize, Vec<String>)> = audit(&input)
+      .into_iter()
+      .map(|(line, found)| (line, found.iter().map(|p| p.to_string()).collect()))
+      .collect();
+    assert_eq!(
+      report,
+      vec![
+        (1, vec!["odd length 5".to_string()]),
+        (
+          2,
+          vec![
+            "invalid characters `-1`".to_string(),
+            "several items shared: a (1), b (2)".to_string()
+          ]
+        ),
+        (3, vec!["no item shared by the compartments".to_string()]),
+        (4, vec!["several items shared: a (1), B (28)".to_string()]),
+      ]
+    );
+  }
+
+  const SAMPLE: &str = indoc! {"
+    vJrwpWtwJgWrhcsFMMfFFhFp
+    jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+    PmmdzqPrVvPwwTWBwg
+    wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+    ttgJtRGJQctTZtZT
+    CrZsJsPPZsGzwwsLwLmpwMDw
+  "};
 }
@@ -0,0 +1,171 @@
+/// Handle to a node of a `Tree`. Only meaningful for the tree that issued it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+impl NodeId {
+  /// Dense position of the node, handy to index per-node vectors.
+  pub fn index(&self) -> usize {
+    self.0
+  }
+}
+
+#[derive(Debug, Clone)]
+struct TreeNode<T> {
+  name: String,
+  parent: Option<NodeId>,
+  children: Vec<NodeId>,
+  data: T,
+}
+
+/// Rooted tree of named nodes stored in a single vector. Parents and children
+/// refer to each other by id, so there are no reference cycles to leak.
+#[derive(Debug, Clone)]
+pub struct Tree<T> {
+  nodes: Vec<TreeNode<T>>,
+}
+
+impl<T> Tree<T> {
+  pub fn new(root_name: &str, data: T) -> Self {
+    Self {
+      nodes: vec![TreeNode {
+        name: root_name.to_string(),
+        parent: None,
+        children: vec![],
+        data,
+      }],
+    }
+  }
+
+  pub fn root(&self) -> NodeId {
+    NodeId(0)
+  }
+
+  pub fn len(&self) -> usize {
+    self.nodes.len()
+  }
+
+  pub fn ids(&self) -> impl Iterator<Item = NodeId> {
+    (0..self.len()).map(NodeId)
+  }
+
+  pub fn get(&self, id: NodeId) -> &T {
+    &self.nodes[id.0].data
+  }
+
+  pub fn name(&self, id: NodeId) -> &str {
+    &self.nodes[id.0].name
+  }
+
+  pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+    self.nodes[id.0].parent
+  }
+
+  pub fn children(&self, id: NodeId) -> &[NodeId] {
+    &self.nodes[id.0].children
+  }
+
+  pub fn child(&self, id: NodeId, name: &str) -> Option<NodeId> {
+    self
+      .children(id)
+      .iter()
+      .copied()
+      .find(|&c| self.name(c) == name)
+  }
+
+  /// Adds a child to `parent`, or replaces the data of the child already
+  /// using `name`.
+  pub fn add_child(&mut self, parent: NodeId, name: &str, data: T) -> NodeId {
+    if let Some(id) = self.child(parent, name) {
+      self.nodes[id.0].data = data;
+      return id;
+    }
+    let id = NodeId(self.nodes.len());
+    self.nodes.push(TreeNode {
+      name: name.to_string(),
+      parent: Some(parent),
+      children: vec![],
+      data,
+    });
+    self.nodes[parent.0].children.push(id);
+    id
+  }
+
+  /// Nodes below `id`, `id` included, every node coming after all of its
+  /// descendants.
+  pub fn post_order(&self, id: NodeId) -> Vec<NodeId> {
+    // reversed pre-order visiting children right to left
+    let mut order = vec![];
+    let mut stack = vec![id];
+    while let Some(current) = stack.pop() {
+      order.push(current);
+      stack.extend(self.children(current));
+    }
+    order.reverse();
+    order
+  }
+
+  /// Follows a `/` separated path. A leading `/` starts at the root, `..` goes
+  /// to the parent and `.` or empty segments stay put.
+  pub fn resolve(&self, from: NodeId, path: &str) -> Option<NodeId> {
+    let start = if path.starts_with('/') {
+      self.root()
+    } else {
+      from
+    };
+    path
+      .split('/')
+      .try_fold(start, |current, segment| match segment {
+        "" | "." => Some(current),
+        ".." => self.parent(current),
+        name => self.child(current, name),
+      })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Tree;
+
+  // /
+  // ├── a
+  // │   └── e
+  // └── b
+  fn sample() -> Tree<usize> {
+    let mut tree = Tree::new("/", 0);
+    let a = tree.add_child(tree.root(), "a", 1);
+    tree.add_child(tree.root(), "b", 2);
+    tree.add_child(a, "e", 3);
+    tree
+  }
+
+  #[test]
+  fn navigation() {
+    let mut tree = sample();
+    let root = tree.root();
+    let a = tree.child(root, "a").unwrap();
+    let e = tree.resolve(root, "a/e").unwrap();
+    assert_eq!(tree.len(), 4);
+    assert_eq!(*tree.get(e), 3);
+    assert_eq!(tree.parent(e), Some(a));
+    assert_eq!(tree.parent(root), None);
+    assert_eq!(tree.resolve(e, "../../b"), tree.child(root, "b"));
+    assert_eq!(tree.resolve(e, "/"), Some(root));
+    assert_eq!(tree.resolve(e, "./"), Some(e));
+    assert_eq!(tree.resolve(root, "z"), None);
+    assert_eq!(tree.resolve(root, ".."), None);
+
+    assert_eq!(tree.add_child(root, "a", 10), a);
+    assert_eq!((*tree.get(a), *tree.get(e)), (10, 3));
+  }
+
+  #[test]
+  fn post_order() {
+    let tree = sample();
+    let names: Vec<&str> = tree
+      .post_order(tree.root())
+      .into_iter()
+      .map(|id| tree.name(id))
+      .collect();
+    assert_eq!(names, vec!["e", "a", "b", "/"]);
+  }
+}
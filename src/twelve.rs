@@ -1,5 +1,5 @@
-use crate::point::{Point, DIRECTIONS};
-use std::collections::HashSet;
+use crate::point::Point;
+use crate::search::bfs;
 use std::fs::File;
 use std::io::{prelude::*, BufReader};
 
@@ -18,7 +18,6 @@ impl MapCell {
       'S' => Self::Start,
       'E' => Self::End,
       n => Self::Empty(n as usize),
-      _ => unreachable!(),
     }
   }
 
@@ -43,117 +42,59 @@ fn read_data() -> Input {
   line_iter.map(|l| l.unwrap()).collect()
 }
 
-const ZERO: Point = Point::new(0, 0);
+type Grid = Vec<Vec<MapCell>>;
 
-fn solve(input: Input) -> usize {
-  let mut visited: HashSet<Point> = HashSet::new();
-  let grid: Vec<Vec<MapCell>> = input
+fn parse_grid(input: &Input) -> Grid {
+  input
     .iter()
-    .map(|l| l.chars().map(|c| MapCell::from_char(c)).collect())
-    .collect();
-  let H: usize = grid.len();
-  let W: usize = grid[0].len();
-  let dimensions = Point::new(H, W);
-  let mut starting_point: Point = Point::new(0, 0);
+    .map(|l| l.chars().map(MapCell::from_char).collect())
+    .collect()
+}
+
+fn find_cell(grid: &Grid, target: MapCell) -> Point {
   for (x, row) in grid.iter().enumerate() {
-    for (y, cell) in row.iter().enumerate() {
-      if let MapCell::Start = cell {
-        starting_point = Point::new(x, y);
-        break;
-      }
-    }
-    if starting_point != ZERO {
-      break;
+    if let Some(y) = row.iter().position(|&cell| cell == target) {
+      return Point::new(x, y);
     }
   }
+  unreachable!()
+}
 
-  let mut open: HashSet<Point> = HashSet::new();
-  open.insert(starting_point);
-  let mut score: usize = 0;
-
-  loop {
-    score += 1;
-    let mut new_open: HashSet<Point> = HashSet::new();
-    for point in open.iter() {
-      visited.insert(*point);
-      let old = &grid[point.x][point.y];
-      for new_point in point.get_points_around_inside_container(&dimensions) {
-        if visited.contains(&new_point) {
-          continue;
-        }
-        let cell: &MapCell = &grid[new_point.x][new_point.y];
-        match cell {
-          MapCell::End => {
-            if old.can_proceed_to(cell) {
-              return score;
-            }
-          }
-          MapCell::Empty(_) => {
-            if old.can_proceed_to(cell) {
-              new_open.insert(new_point);
-            }
-          }
-          _ => (),
-        }
-      }
-    }
-    open = new_open;
-  }
+fn solve(input: Input) -> usize {
+  let grid = &parse_grid(&input);
+  let dimensions = Point::new(grid.len(), grid[0].len());
+  let starting_point = find_cell(grid, MapCell::Start);
+
+  let neighbours = |point: &Point| {
+    let old = grid[point.x][point.y];
+    point
+      .get_points_around_inside_container(&dimensions)
+      .into_iter()
+      .filter(move |p| old.can_proceed_to(&grid[p.x][p.y]))
+  };
+  let is_goal = |point: &Point| grid[point.x][point.y] == MapCell::End;
+  bfs([starting_point], neighbours, is_goal).unwrap().distance
 }
 
 fn solve_v2(input: Input) -> usize {
-  let mut visited: HashSet<Point> = HashSet::new();
-  let grid: Vec<Vec<MapCell>> = input
-    .iter()
-    .map(|l| l.chars().map(|c| MapCell::from_char(c)).collect())
-    .collect();
-  let H: usize = grid.len();
-  let W: usize = grid[0].len();
-  let dimensions = Point::new(H, W);
-  let mut starting_point: Point = Point::new(0, 0);
-  for (x, row) in grid.iter().enumerate() {
-    for (y, cell) in row.iter().enumerate() {
-      if let MapCell::End = cell {
-        starting_point = Point::new(x, y);
-        break;
-      }
-    }
-    if starting_point != ZERO {
-      break;
-    }
-  }
-
-  let mut open: HashSet<Point> = HashSet::new();
-  open.insert(starting_point);
-  let mut score: usize = 0;
-
-  loop {
-    score += 1;
-    let mut new_open: HashSet<Point> = HashSet::new();
-    for point in open.iter() {
-      visited.insert(*point);
-      const TARGET: MapCell = MapCell::from_char('a');
-      let old = &grid[point.x][point.y];
-      if old.eq(&TARGET) {
-        return score - 1;
-      }
-      for new_point in point.get_points_around_inside_container(&dimensions) {
-        if visited.contains(&new_point) {
-          continue;
-        }
-        let cell: &MapCell = &grid[new_point.x][new_point.y];
-        match cell {
-          MapCell::Empty(_) => {
-            if cell.can_proceed_to(old) {
-              new_open.insert(new_point);
-            }
-          }
-          _ => (),
-        }
-      }
-    }
-    open = new_open;
-  }
+  const TARGET: MapCell = MapCell::from_char('a');
+  let grid = &parse_grid(&input);
+  let dimensions = Point::new(grid.len(), grid[0].len());
+  let starting_point = find_cell(grid, MapCell::End);
+
+  //walk backwards from the end
+  let neighbours = |point: &Point| {
+    let old = grid[point.x][point.y];
+    point
+      .get_points_around_inside_container(&dimensions)
+      .into_iter()
+      .filter(move |p| {
+        let cell = grid[p.x][p.y];
+        matches!(cell, MapCell::Empty(_)) && cell.can_proceed_to(&old)
+      })
+  };
+  let is_goal = |point: &Point| grid[point.x][point.y] == TARGET;
+  bfs([starting_point], neighbours, is_goal).unwrap().distance
 }
 
 pub fn twelve() {
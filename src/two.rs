@@ -1,158 +1,675 @@
+use crate::cli::{Options, Part};
+use crate::parser::{parse_line, ParseError, ParseResult, Parser};
 use std::cmp::{Eq, PartialEq};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
 use std::fs::File;
 use std::io::{prelude::*, BufReader};
 
+const DEFAULT_GAME: &str = include_str!("../resources/games/rock-paper-scissors.txt");
+
+// Index of a shape in `Game::shapes`.
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
-enum Choice {
-  Rock,
-  Paper,
-  Scissor,
-}
+struct Choice(usize);
 
+// Letter of the second column of the guide.
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
-enum Strategy {
-  X,
-  Y,
-  Z,
-}
-
-impl From<&str> for Choice {
-  fn from(i: &str) -> Self {
-    match i {
-      "A" => Choice::Rock,
-      "B" => Choice::Paper,
-      "C" => Choice::Scissor,
-      _ => unreachable!(),
+struct Strategy(char);
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+enum Outcome {
+  Lost,
+  Draw,
+  Won,
+}
+
+impl Outcome {
+  // Spelling used by the `strategy:` line of game configs.
+  fn verb(&self) -> &'static str {
+    match self {
+      Outcome::Lost => "lose",
+      Outcome::Draw => "draw",
+      Outcome::Won => "win",
     }
   }
 }
 
-impl From<usize> for Choice {
-  fn from(i: usize) -> Self {
-    match i % 3 {
-      0 => Choice::Rock,
-      1 => Choice::Paper,
-      2 => Choice::Scissor,
-      _ => unreachable!(),
+/// Rules of a game where each player shows a shape, read from a config file
+/// such as `resources/games/rock-paper-scissors.txt`.
+#[derive(Debug, Clone)]
+struct Game {
+  shapes: Vec<String>,
+  scores: Vec<usize>,
+  // beats[a][b]: shape `a` wins against shape `b`
+  beats: Vec<Vec<bool>>,
+  // indexed by `Outcome`
+  outcome_scores: [usize; 3],
+  opponent: Vec<char>,
+  player: Vec<char>,
+  strategy: Vec<(char, Outcome)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum GameError {
+  Missing(&'static str),
+  Parse(ParseError),
+}
+
+impl Display for GameError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      GameError::Missing(key) => write!(f, "missing `{key}:` line"),
+      GameError::Parse(e) => write!(f, "{e}"),
     }
   }
 }
 
-impl Into<usize> for Choice {
-  fn into(self) -> usize {
-    match self {
-      Choice::Rock => 0,
-      Choice::Paper => 1,
-      Choice::Scissor => 2,
+impl std::error::Error for GameError {}
+
+impl From<ParseError> for GameError {
+  fn from(e: ParseError) -> Self {
+    GameError::Parse(e)
+  }
+}
+
+const KEYS: [&str; 7] = [
+  "shapes", "scores", "beats", "outcomes", "opponent", "player", "strategy",
+];
+
+// `key: value` lines of a config file, by key.
+struct Config<'a> {
+  lines: HashMap<&'a str, (usize, &'a str)>,
+}
+
+impl<'a> Config<'a> {
+  fn new(text: &'a str) -> Result<Self, GameError> {
+    let mut lines = HashMap::new();
+    for (i, line) in text.lines().enumerate() {
+      let trimmed = line.trim();
+      if trimmed.is_empty() || trimmed.starts_with('#') {
+        continue;
+      }
+      let error =
+        |message: String| GameError::from(Parser::new(line).error(message).at_line(i + 1));
+      let key = Parser::new(line).word().map_err(|e| e.at_line(i + 1))?;
+      let Some(&key) = KEYS.iter().find(|&&k| k == key) else {
+        return Err(error(format!("unknown key `{key}`")));
+      };
+      if lines.insert(key, (i + 1, line)).is_some() {
+        return Err(error(format!("duplicate key `{key}`")));
+      }
+    }
+    Ok(Self { lines })
+  }
+
+  fn field<T>(
+    &self,
+    key: &'static str,
+    f: impl FnOnce(&mut Parser<'a>) -> ParseResult<T>,
+  ) -> Result<T, GameError> {
+    let &(number, line) = self.lines.get(key).ok_or(GameError::Missing(key))?;
+    let value = parse_line(line, |p| {
+      p.keyword(key)?;
+      p.literal(":")?;
+      f(p)
+    });
+    Ok(value.map_err(|e| e.at_line(number))?)
+  }
+}
+
+// Items up to the end of the line, at least one.
+fn list<'a, T>(
+  p: &mut Parser<'a>,
+  mut item: impl FnMut(&mut Parser<'a>) -> ParseResult<T>,
+) -> ParseResult<Vec<T>> {
+  let mut items = vec![item(p)?];
+  while !p.is_done() {
+    items.push(item(p)?);
+  }
+  Ok(items)
+}
+
+fn letter(p: &mut Parser) -> ParseResult<char> {
+  p.skip_whitespace();
+  let err = p.error("expected a single letter");
+  let word = p.word()?;
+  let mut chars = word.chars();
+  match (chars.next(), chars.next()) {
+    (Some(c), None) => Ok(c),
+    _ => Err(err),
+  }
+}
+
+fn shape(p: &mut Parser, shapes: &[String]) -> ParseResult<usize> {
+  p.skip_whitespace();
+  let err = p.error("unknown shape");
+  let name = p.word()?;
+  shapes.iter().position(|s| s == name).ok_or(err)
+}
+
+fn expect_len<T>(p: &Parser, items: Vec<T>, len: usize, what: &str) -> ParseResult<Vec<T>> {
+  if items.len() == len {
+    Ok(items)
+  } else {
+    Err(p.error(format!("expected {len} {what}, found {}", items.len())))
+  }
+}
+
+fn letters(p: &mut Parser, len: usize) -> ParseResult<Vec<char>> {
+  let letters = list(p, letter)?;
+  for (i, c) in letters.iter().enumerate() {
+    if letters[..i].contains(c) {
+      return Err(p.error(format!("letter `{c}` is used twice")));
     }
   }
+  expect_len(p, letters, len, "letters")
 }
 
-impl From<&str> for Strategy {
-  fn from(i: &str) -> Self {
-    match i {
-      "X" => Strategy::X,
-      "Y" => Strategy::Y,
-      "Z" => Strategy::Z,
-      _ => unreachable!(),
+impl Game {
+  fn from_config(text: &str) -> Result<Self, GameError> {
+    let config = Config::new(text)?;
+    let shapes: Vec<String> = config.field("shapes", |p| {
+      let names = list(p, |p| p.word().map(str::to_string))?;
+      for (i, name) in names.iter().enumerate() {
+        if names[..i].contains(name) {
+          return Err(p.error(format!("shape `{name}` is listed twice")));
+        }
+      }
+      Ok(names)
+    })?;
+    let n = shapes.len();
+    let scores = config.field("scores", |p| {
+      let scores = list(p, Parser::unsigned)?;
+      expect_len(p, scores, n, "scores")
+    })?;
+    let beats = config.field("beats", |p| {
+      p.skip_whitespace();
+      let start = p.clone();
+      if p.try_keyword("cyclic") {
+        // with an even count, opposite shapes would beat each other
+        if n.is_multiple_of(2) {
+          return Err(start.error(format!(
+            "cyclic games need an odd number of shapes, not {n}"
+          )));
+        }
+        // every shape beats those an odd number of places before it
+        return Ok(
+          (0..n)
+            .map(|a| (0..n).map(|b| (a + n - b) % n % 2 == 1).collect())
+            .collect(),
+        );
+      }
+      let mut beats = vec![vec![false; n]; n];
+      while !p.is_done() {
+        let start = p.clone();
+        let a = shape(p, &shapes)?;
+        p.literal(">")?;
+        let b = shape(p, &shapes)?;
+        if a == b || beats[b][a] {
+          return Err(start.error(format!("{} cannot beat {}", shapes[a], shapes[b])));
+        }
+        beats[a][b] = true;
+      }
+      Ok(beats)
+    })?;
+    let outcomes = config.field("outcomes", |p| {
+      let scores = list(p, Parser::unsigned)?;
+      expect_len(p, scores, 3, "outcome scores (lost, draw, won)")
+    })?;
+    let opponent = config.field("opponent", |p| letters(p, n))?;
+    let player = config.field("player", |p| letters(p, n))?;
+    let strategy = config.field("strategy", |p| {
+      list(p, |p| {
+        let c = letter(p)?;
+        p.literal("=")?;
+        let outcome = p.one_of(&[
+          ("lose", Outcome::Lost),
+          ("draw", Outcome::Draw),
+          ("win", Outcome::Won),
+        ])?;
+        Ok((c, outcome))
+      })
+    })?;
+    Ok(Self {
+      shapes,
+      scores,
+      beats,
+      outcome_scores: [outcomes[0], outcomes[1], outcomes[2]],
+      opponent,
+      player,
+      strategy,
+    })
+  }
+
+  fn rock_paper_scissors() -> Self {
+    Self::from_config(DEFAULT_GAME).unwrap()
+  }
+
+  fn outcome(&self, opponent: Choice, mine: Choice) -> Outcome {
+    if self.beats[mine.0][opponent.0] {
+      Outcome::Won
+    } else if self.beats[opponent.0][mine.0] {
+      Outcome::Lost
+    } else {
+      Outcome::Draw
     }
   }
+
+  fn outcome_score(&self, outcome: Outcome) -> usize {
+    self.outcome_scores[outcome as usize]
+  }
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
 struct PlannedMove(Choice, Strategy);
 
 impl PlannedMove {
-  fn from_line(l: &str) -> Self {
-    let chars: Vec<&str> = l.split(" ").collect();
-    let choice = Choice::from(chars[0]);
-    let strategy = Strategy::from(chars[1]);
-    Self(choice, strategy)
-  }
-
-  fn into_move(&self) -> Move {
-    let my_choice = match self.1 {
-      Strategy::X => Choice::Rock,
-      Strategy::Y => Choice::Paper,
-      Strategy::Z => Choice::Scissor,
-    };
-    Move(self.0, my_choice)
+  fn parse(p: &mut Parser, game: &Game) -> ParseResult<Self> {
+    p.skip_whitespace();
+    let err = p.error("unknown opponent letter");
+    let c = letter(p)?;
+    let choice = game.opponent.iter().position(|&o| o == c).ok_or(err)?;
+    p.skip_whitespace();
+    let err = p.error("unknown strategy letter");
+    let s = letter(p)?;
+    let known = game.player.contains(&s) || game.strategy.iter().any(|&(l, _)| l == s);
+    if !known {
+      return Err(err);
+    }
+    Ok(Self(Choice(choice), Strategy(s)))
   }
 
-  fn into_move_extra(&self) -> Move {
-    let my_choice = match self.1 {
-      Strategy::X => {
-        let x: usize = self.0.into();
-        Choice::from(x + 2)
-      }
-      Strategy::Y => self.0.clone(),
-      Strategy::Z => {
-        let x: usize = self.0.into();
-        Choice::from(x + 1)
-      }
-    };
+  fn from_line(l: &str, game: &Game) -> Self {
+    parse_line(l, |p| PlannedMove::parse(p, game)).unwrap()
+  }
+
+  fn into_move(self, game: &Game) -> Move {
+    let Strategy(letter) = self.1;
+    let my_choice = game
+      .player
+      .iter()
+      .position(|&c| c == letter)
+      .unwrap_or_else(|| panic!("`{letter}` does not name a shape"));
+    Move(self.0, Choice(my_choice))
+  }
+
+  fn into_move_extra(self, game: &Game) -> Move {
+    let Strategy(letter) = self.1;
+    let (_, outcome) = game
+      .strategy
+      .iter()
+      .find(|&&(c, _)| c == letter)
+      .unwrap_or_else(|| panic!("`{letter}` does not name an outcome"));
+    let my_choice = (0..game.shapes.len())
+      .map(Choice)
+      .find(|&c| game.outcome(self.0, c) == *outcome)
+      .unwrap_or_else(|| {
+        panic!(
+          "no shape can {outcome:?} against {}",
+          game.shapes[self.0 .0]
+        )
+      });
     Move(self.0, my_choice)
   }
+
+  fn interpret(&self, game: &Game, part: Part) -> Move {
+    match part {
+      Part::One => self.into_move(game),
+      Part::Two => self.into_move_extra(game),
+    }
+  }
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
 struct Move(Choice, Choice);
 
-const LOST: usize = 0;
-const DRAW: usize = 3;
-const WON: usize = 6;
-
 impl Move {
-  fn score(&self) -> usize {
-    self.score_by_shape() + self.score_by_outcome()
+  fn score(&self, game: &Game) -> usize {
+    self.score_by_shape(game) + self.score_by_outcome(game)
   }
 
-  fn score_by_shape(&self) -> usize {
-    match self.1 {
-      Choice::Rock => 1,
-      Choice::Scissor => 3,
-      Choice::Paper => 2,
-    }
+  fn score_by_shape(&self, game: &Game) -> usize {
+    game.scores[self.1 .0]
   }
 
-  fn score_by_outcome(&self) -> usize {
-    if self.0 == self.1 {
-      return DRAW;
-    }
-    if self.0 == Choice::Rock && self.1 == Choice::Scissor {
-      return LOST;
-    }
-    if self.0 == Choice::Scissor && self.1 == Choice::Paper {
-      return LOST;
-    }
-    if self.0 == Choice::Paper && self.1 == Choice::Rock {
-      return LOST;
-    }
-    WON
+  fn outcome(&self, game: &Game) -> Outcome {
+    game.outcome(self.0, self.1)
+  }
+
+  fn score_by_outcome(&self, game: &Game) -> usize {
+    game.outcome_score(self.outcome(game))
   }
 }
 
-fn read_data() -> Vec<PlannedMove> {
+fn read_data(game: &Game) -> Vec<PlannedMove> {
   let filename = format!("./resources/2.txt");
   let file: File = File::open(&filename).expect(&format!("Cannot open file {}", &filename));
   let reader = BufReader::new(file);
   let line_iter = reader.lines();
   line_iter
     .map(|l| l.unwrap())
-    .map(|l| PlannedMove::from_line(&l))
+    .map(|l| PlannedMove::from_line(&l, game))
     .collect()
 }
 
-fn eval(game: &Vec<PlannedMove>) -> usize {
-  game
+fn eval(game: &Game, guide: &[PlannedMove], part: Part) -> usize {
+  guide
     .iter()
-    .map(|m| m.into_move_extra())
-    .map(|m| m.score())
+    .map(|m| m.interpret(game, part))
+    .map(|m| m.score(game))
     .sum()
 }
 
-pub fn two() {
-  let game = read_data();
-  let score = eval(&game);
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct ScoreReport {
+  shape: usize,
+  outcome: usize,
+  won: usize,
+  drawn: usize,
+  lost: usize,
+}
+
+impl ScoreReport {
+  fn total(&self) -> usize {
+    self.shape + self.outcome
+  }
+}
+
+impl Display for ScoreReport {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    writeln!(f, "shape:   {:>8}", self.shape)?;
+    writeln!(
+      f,
+      "outcome: {:>8}  ({} won, {} drawn, {} lost)",
+      self.outcome, self.won, self.drawn, self.lost
+    )?;
+    write!(f, "total:   {:>8}", self.total())
+  }
+}
+
+fn report(game: &Game, guide: &[PlannedMove], part: Part) -> ScoreReport {
+  let mut report = ScoreReport::default();
+  for m in guide.iter().map(|m| m.interpret(game, part)) {
+    report.shape += m.score_by_shape(game);
+    report.outcome += m.score_by_outcome(game);
+    match m.outcome(game) {
+      Outcome::Won => report.won += 1,
+      Outcome::Draw => report.drawn += 1,
+      Outcome::Lost => report.lost += 1,
+    }
+  }
+  report
+}
+
+// Every ordering of `0..n`, in lexicographic order.
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+  if n == 0 {
+    return vec![vec![]];
+  }
+  let mut result = vec![];
+  for first in 0..n {
+    for rest in permutations(n - 1) {
+      let mut perm = vec![first];
+      perm.extend(rest.into_iter().map(|i| if i >= first { i + 1 } else { i }));
+      result.push(perm);
+    }
+  }
+  result
+}
+
+/// A reading of the guide's second column: which shape (part one) or which
+/// outcome (part two) each letter stands for, and what it scores.
+#[derive(Debug, Clone)]
+struct Assignment {
+  part: Part,
+  game: Game,
+  total: usize,
+  moves: Vec<Move>,
+}
+
+impl Assignment {
+  fn new(game: Game, guide: &[PlannedMove], part: Part) -> Self {
+    let moves: Vec<Move> = guide.iter().map(|m| m.interpret(&game, part)).collect();
+    let total = moves.iter().map(|m| m.score(&game)).sum();
+    Self {
+      part,
+      game,
+      total,
+      moves,
+    }
+  }
+
+  fn mapping(&self) -> String {
+    let game = &self.game;
+    let pairs: Vec<String> = match self.part {
+      Part::One => game
+        .player
+        .iter()
+        .zip(&game.shapes)
+        .map(|(l, s)| format!("{l}={s}"))
+        .collect(),
+      Part::Two => game
+        .strategy
+        .iter()
+        .map(|(l, o)| format!("{l}={}", o.verb()))
+        .collect(),
+    };
+    pairs.join(" ")
+  }
+
+  fn rounds(&self) -> impl Iterator<Item = String> + '_ {
+    let game = &self.game;
+    self.moves.iter().map(move |m| {
+      format!(
+        "{} vs {}: {:?}, {}",
+        game.shapes[m.1 .0],
+        game.shapes[m.0 .0],
+        m.outcome(game),
+        m.score(game)
+      )
+    })
+  }
+}
+
+impl Display for Assignment {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    let part = match self.part {
+      Part::One => "one",
+      Part::Two => "two",
+    };
+    write!(f, "part {part}, {}: {}", self.mapping(), self.total)
+  }
+}
+
+// Every shape assignment under part one and every outcome assignment under
+// part two.
+fn assignments(game: &Game, guide: &[PlannedMove]) -> Vec<Assignment> {
+  let mut result = vec![];
+  for perm in permutations(game.player.len()) {
+    let mut remapped = game.clone();
+    remapped.player = perm.iter().map(|&i| game.player[i]).collect();
+    result.push(Assignment::new(remapped, guide, Part::One));
+  }
+  for perm in permutations(game.strategy.len()) {
+    let mut remapped = game.clone();
+    remapped.strategy = (0..perm.len())
+      .map(|i| (game.strategy[i].0, game.strategy[perm[i]].1))
+      .collect();
+    result.push(Assignment::new(remapped, guide, Part::Two));
+  }
+  result
+}
+
+// Highest and lowest scoring assignments of each part, part one first, the
+// first one found on ties.
+fn optimize(game: &Game, guide: &[PlannedMove]) -> Vec<(Assignment, Assignment)> {
+  let all = assignments(game, guide);
+  [Part::One, Part::Two]
+    .into_iter()
+    .map(|part| {
+      let mut candidates = all.iter().filter(|a| a.part == part);
+      let first = candidates.next().unwrap();
+      let (mut best, mut worst) = (first, first);
+      for a in candidates {
+        if a.total > best.total {
+          best = a;
+        }
+        if a.total < worst.total {
+          worst = a;
+        }
+      }
+      (best.clone(), worst.clone())
+    })
+    .collect()
+}
+
+fn load_game(options: &Options) -> Game {
+  let Some(path) = options.value("game") else {
+    return Game::rock_paper_scissors();
+  };
+  let text =
+    std::fs::read_to_string(path).unwrap_or_else(|e| panic!("Cannot open file {path}: {e}"));
+  Game::from_config(&text).unwrap_or_else(|e| panic!("{path}: {e}"))
+}
+
+pub fn two(options: &Options) {
+  let game = load_game(options);
+  let guide = read_data(&game);
+  let part = options.part();
+  if options.flag("report") {
+    println!("{}", report(&game, &guide, part));
+    return;
+  }
+  if options.flag("optimize") {
+    for (best, worst) in optimize(&game, &guide) {
+      for (label, a) in [("best", best), ("worst", worst)] {
+        println!("{label}: {a}");
+        if options.flag("rounds") {
+          a.rounds().for_each(|r| println!("  {r}"));
+        }
+      }
+    }
+    return;
+  }
+  let score = eval(&game, &guide, part);
   println!("{score}");
 }
+
+#[cfg(test)]
+mod tests {
+  use super::{assignments, eval, optimize, permutations, report, Game, GameError, PlannedMove};
+  use crate::cli::Part;
+
+  fn guide(game: &Game, text: &str) -> Vec<PlannedMove> {
+    text
+      .lines()
+      .map(|l| PlannedMove::from_line(l, game))
+      .collect()
+  }
+
+  fn sample() -> Vec<PlannedMove> {
+    guide(&Game::rock_paper_scissors(), SAMPLE)
+  }
+
+  #[test]
+  fn simple() {
+    let game = Game::rock_paper_scissors();
+    assert_eq!(eval(&game, &sample(), Part::One), 15);
+  }
+
+  #[test]
+  fn v2() {
+    let game = Game::rock_paper_scissors();
+    assert_eq!(eval(&game, &sample(), Part::Two), 12);
+  }
+
+  #[test]
+  fn lizard_spock() {
+    let config = include_str!("../resources/games/rock-paper-scissors-lizard-spock.txt");
+    let game = Game::from_config(config).unwrap();
+    // rock draws rock, spock beats rock, lizard beats spock
+    let moves = guide(&game, "A V\nA Y\nD Z");
+    assert_eq!(eval(&game, &moves, Part::One), 4 + 10 + 11);
+    // losing to scissors: paper comes before lizard
+    let moves = guide(&game, "C X");
+    assert_eq!(eval(&game, &moves, Part::Two), 2);
+  }
+
+  #[test]
+  fn config_errors() {
+    let valid = super::DEFAULT_GAME;
+    let err = |text: &str| Game::from_config(text).unwrap_err().to_string();
+    assert_eq!(
+      err(&valid.replace("scores: 1 2 3", "scores: 1 2")),
+      "line 4, column 12: expected 3 scores, found 2"
+    );
+    assert_eq!(
+      err(&valid.replace("Paper>Rock", "Paper>Stone")),
+      "line 5, column 28: unknown shape"
+    );
+    assert_eq!(
+      err(&valid.replace("Scissors>Paper", "Rock>Paper Paper>Rock")),
+      "line 5, column 33: Rock cannot beat Paper"
+    );
+    let four = valid
+      .replace("Rock Paper Scissors", "Rock Paper Scissors Well")
+      .replace("1 2 3", "1 2 3 4")
+      .replace("Rock>Scissors Paper>Rock Scissors>Paper", "cyclic");
+    assert_eq!(
+      err(&four),
+      "line 5, column 8: cyclic games need an odd number of shapes, not 4"
+    );
+    assert_eq!(
+      err(&valid.replace("player", "playr")),
+      "line 8, column 1: unknown key `playr`"
+    );
+    assert_eq!(
+      Game::from_config(&valid.replace("outcomes: 0 3 6", "")).unwrap_err(),
+      GameError::Missing("outcomes")
+    );
+  }
+
+  #[test]
+  fn orderings() {
+    assert_eq!(permutations(0), vec![Vec::<usize>::new()]);
+    let perms = permutations(3);
+    assert_eq!(perms.len(), 6);
+    assert_eq!(perms[1], vec![0, 2, 1]);
+    assert_eq!(perms[5], vec![2, 1, 0]);
+  }
+
+  #[test]
+  fn optimizer() {
+    let game = Game::rock_paper_scissors();
+    let guide = sample();
+    let all = assignments(&game, &guide);
+    assert_eq!(all.len(), 12);
+    // the identity readings are the puzzle's own answers
+    assert_eq!((all[0].total, all[6].total), (15, 12));
+    let extremes = optimize(&game, &guide);
+    let (best, worst) = &extremes[0];
+    assert_eq!(best.to_string(), "part one, Z=Rock Y=Paper X=Scissors: 24");
+    assert_eq!(worst.to_string(), "part one, X=Rock Z=Paper Y=Scissors: 6");
+    let (best_v2, worst_v2) = &extremes[1];
+    assert_eq!(best_v2.to_string(), "part two, X=win Y=lose Z=draw: 18");
+    assert_eq!(worst_v2.to_string(), "part two, X=lose Y=draw Z=win: 12");
+    let rounds: Vec<String> = best.rounds().collect();
+    assert_eq!(rounds[0], "Paper vs Rock: Won, 8");
+  }
+
+  #[test]
+  fn breakdown() {
+    let game = Game::rock_paper_scissors();
+    let r = report(&game, &sample(), Part::One);
+    assert_eq!((r.shape, r.outcome), (6, 9));
+    assert_eq!((r.won, r.drawn, r.lost), (1, 1, 1));
+    let r = report(&game, &sample(), Part::Two);
+    assert_eq!((r.shape, r.outcome, r.total()), (3, 9, 12));
+    assert_eq!(
+      r.to_string(),
+      "shape:          3\noutcome:        9  (1 won, 1 drawn, 1 lost)\ntotal:         12"
+    );
+  }
+
+  const SAMPLE: &str = "A Y\nB X\nC Z";
+}